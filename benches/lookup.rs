@@ -0,0 +1,111 @@
+//! Compares the generated perfect hash map against binary search over the
+//! sorted tables it replaced, rebuilt from the public API.
+//!
+//! Run with `cargo bench --bench lookup`.
+use std::cmp::Ordering;
+
+use compressible::{compressible_types, incompressible_types, Compressibility};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const HITS: &[&str] = &[
+    "text/html",
+    "application/json",
+    "image/svg+xml",
+    "image/jpeg",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+];
+
+const MISSES: &[&str] = &[
+    "a/b",
+    "image/webp",
+    "text/x-made-up",
+    "application/vnd.acme.report+json",
+    "zzzz/zzzz",
+];
+
+const PARAMETERISED: &[&str] = &[
+    "text/html; charset=utf-8",
+    "application/json;charset=utf-8",
+    "text/plain; charset=us-ascii; format=flowed",
+    "image/png; name=logo.png",
+];
+
+/// The sorted tables binary search used to run over, rebuilt from the public
+/// API.
+struct Tables {
+    compressible: Vec<&'static str>,
+    incompressible: Vec<&'static str>,
+}
+
+impl Tables {
+    fn new() -> Self {
+        Tables {
+            compressible: compressible_types().collect(),
+            incompressible: incompressible_types().collect(),
+        }
+    }
+
+    /// Matches the way `lookup` normalizes its input: parameters and
+    /// surrounding whitespace are ignored and case does not matter.
+    fn binary_search(&self, content_type: &str) -> Option<bool> {
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+        let search = |table: &[&str]| {
+            table
+                .binary_search_by(|candidate| cmp_ignore_ascii_case(candidate, essence))
+                .is_ok()
+        };
+        if search(&self.compressible) {
+            Some(true)
+        } else if search(&self.incompressible) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+fn cmp_ignore_ascii_case(a: &str, b: &str) -> Ordering {
+    a.bytes()
+        .map(|b| b.to_ascii_lowercase())
+        .cmp(b.bytes().map(|b| b.to_ascii_lowercase()))
+}
+
+fn phf(content_type: &str) -> Option<bool> {
+    match compressible::lookup(content_type).map(|info| info.compressibility) {
+        Some(Compressibility::Yes) => Some(true),
+        Some(Compressibility::No) => Some(false),
+        _ => None,
+    }
+}
+
+fn bench_inputs(c: &mut Criterion, tables: &Tables, group: &str, inputs: &[&str]) {
+    let mut group = c.benchmark_group(group);
+    for input in inputs {
+        assert_eq!(phf(input), tables.binary_search(input));
+
+        group.bench_with_input(BenchmarkId::new("phf", input), input, |b, input| {
+            b.iter(|| phf(black_box(input)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("binary_search", input),
+            input,
+            |b, input| b.iter(|| tables.binary_search(black_box(input))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("compressibility", input),
+            input,
+            |b, input| b.iter(|| compressible::compressibility(black_box(input))),
+        );
+    }
+    group.finish();
+}
+
+fn lookup(c: &mut Criterion) {
+    let tables = Tables::new();
+    bench_inputs(c, &tables, "hits", HITS);
+    bench_inputs(c, &tables, "misses", MISSES);
+    bench_inputs(c, &tables, "parameterised", PARAMETERISED);
+}
+
+criterion_group!(benches, lookup);
+criterion_main!(benches);
@@ -0,0 +1,369 @@
+//! Generates the compressible content type and file extension tables from the
+//! vendored mime-db dataset in `data/db.json`.
+//!
+//! Upgrading the dataset is a matter of replacing `data/db.json` with a newer
+//! copy of https://github.com/jshttp/mime-db/blob/master/db.json and
+//! rebuilding, after recording its release, commit and SHA-256 in
+//! `data/db.meta.json`. The metadata describes the exact bytes of
+//! `data/db.json`: the build fails if the SHA-256 does not match, if either file
+//! is malformed, or if the dataset is unsorted or contains duplicate media
+//! types.
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use sha2::{Digest, Sha256};
+
+const DATASET: &str = "data/db.json";
+const METADATA: &str = "data/db.meta.json";
+
+/// Where the vendored dataset was taken from.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Metadata {
+    /// The mime-db release, or `unreleased` for anything else.
+    version: String,
+    /// The full jshttp/mime-db commit hash `data/db.json` is a verbatim copy
+    /// of, or `null` if it is not one.
+    commit: Option<String>,
+    /// The lowercase hex SHA-256 of `data/db.json`.
+    sha256: String,
+}
+
+/// A single media type record from mime-db. Only the fields the crate makes
+/// use of are deserialized.
+#[derive(serde::Deserialize)]
+struct Entry {
+    source: Option<String>,
+    charset: Option<String>,
+    compressible: Option<bool>,
+    #[serde(default)]
+    extensions: Vec<String>,
+}
+
+/// The dataset in file order. Deserialized by hand rather than into a map so
+/// that ordering and duplicate keys can be validated.
+struct Dataset(Vec<(String, Entry)>);
+
+impl<'de> Deserialize<'de> for Dataset {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DatasetVisitor;
+
+        impl<'de> Visitor<'de> for DatasetVisitor {
+            type Value = Dataset;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an object mapping media types to mime-db records")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Dataset, A::Error> {
+                let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(Dataset(entries))
+            }
+        }
+
+        deserializer.deserialize_map(DatasetVisitor)
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={}", DATASET);
+    println!("cargo:rerun-if-changed={}", METADATA);
+    println!("cargo:rustc-check-cfg=cfg(upstream_dataset)");
+
+    let json = fs::read_to_string(DATASET)
+        .unwrap_or_else(|err| panic!("failed to read {}: {}", DATASET, err));
+    let dataset: Dataset = serde_json::from_str(&json)
+        .unwrap_or_else(|err| panic!("failed to parse {}: {}", DATASET, err));
+    let sha256: String = Sha256::digest(json.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    if let Err(err) = validate(&dataset) {
+        panic!("invalid dataset {}: {}", DATASET, err);
+    }
+
+    let json = fs::read_to_string(METADATA)
+        .unwrap_or_else(|err| panic!("failed to read {}: {}", METADATA, err));
+    let metadata: Metadata = serde_json::from_str(&json)
+        .unwrap_or_else(|err| panic!("failed to parse {}: {}", METADATA, err));
+
+    if let Err(err) = validate_metadata(&metadata) {
+        panic!("invalid metadata {}: {}", METADATA, err);
+    }
+    if metadata.sha256 != sha256 {
+        panic!(
+            "{} has SHA-256 {} but {} records {}; update the metadata to describe the new dataset",
+            DATASET, sha256, METADATA, metadata.sha256
+        );
+    }
+
+    // Tests that pin fields the reconstruction lacks only run against a
+    // verbatim upstream copy.
+    if metadata.commit.is_some() {
+        println!("cargo:rustc-cfg=upstream_dataset");
+    } else {
+        println!(
+            "cargo:warning={} is not a verbatim mime-db copy; run scripts/update-dataset.sh",
+            DATASET
+        );
+    }
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    fs::write(
+        Path::new(&out_dir).join("dataset.rs"),
+        generate(&dataset, &metadata, &sha256),
+    )
+    .expect("failed to write generated dataset");
+}
+
+/// Checks that every key is a lowercase `type/subtype` essence with lowercase
+/// extensions and that the keys are strictly ascending, which rules out
+/// duplicates as well.
+fn validate(dataset: &Dataset) -> Result<(), String> {
+    let mut previous: Option<&str> = None;
+
+    for (media_type, entry) in &dataset.0 {
+        if !is_essence(media_type) {
+            return Err(format!("`{}` is not a lowercase media type", media_type));
+        }
+
+        if let Some(source) = &entry.source {
+            if !["apache", "iana", "nginx"].contains(&source.as_str()) {
+                return Err(format!(
+                    "`{}` has an unknown source `{}`",
+                    media_type, source
+                ));
+            }
+        }
+
+        for extension in &entry.extensions {
+            let valid = !extension.is_empty()
+                && extension
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"-_+".contains(&b));
+            if !valid {
+                return Err(format!(
+                    "`{}` has an invalid extension `{}`",
+                    media_type, extension
+                ));
+            }
+        }
+
+        if let Some(previous) = previous {
+            if previous == media_type {
+                return Err(format!("`{}` is listed more than once", media_type));
+            }
+            if previous > media_type.as_str() {
+                return Err(format!(
+                    "`{}` is not sorted, it must come before `{}`",
+                    media_type, previous
+                ));
+            }
+        }
+        previous = Some(media_type);
+    }
+
+    Ok(())
+}
+
+fn validate_metadata(metadata: &Metadata) -> Result<(), String> {
+    let parts: Vec<&str> = metadata.version.split('.').collect();
+    let is_release = parts.len() == 3
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+    if !is_release && metadata.version != "unreleased" {
+        return Err(format!(
+            "version `{}` is neither a release nor `unreleased`",
+            metadata.version
+        ));
+    }
+
+    match &metadata.commit {
+        Some(commit) if !is_lower_hex(commit, 40) => {
+            return Err(format!("commit `{}` is not a full lowercase hash", commit));
+        }
+        None if is_release => {
+            return Err(format!(
+                "release {} must record the commit it was taken from",
+                metadata.version
+            ));
+        }
+        _ => {}
+    }
+
+    if !is_lower_hex(&metadata.sha256, 64) {
+        return Err(format!(
+            "sha256 `{}` is not a lowercase hex digest",
+            metadata.sha256
+        ));
+    }
+
+    Ok(())
+}
+
+fn is_lower_hex(value: &str, len: usize) -> bool {
+    value.len() == len
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+fn is_essence(media_type: &str) -> bool {
+    // RFC 6838 restricted-name characters, lowercased.
+    fn is_name(name: &str) -> bool {
+        !name.is_empty()
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"!#$&-^_.+".contains(&b))
+    }
+
+    match media_type.split_once('/') {
+        Some((type_, subtype)) => is_name(type_) && is_name(subtype),
+        None => false,
+    }
+}
+
+fn generate(dataset: &Dataset, metadata: &Metadata, sha256: &str) -> String {
+    let mut out = String::new();
+    out.push_str("// @generated by build.rs from data/db.json. Do not edit.\n");
+    push_provenance(&mut out, dataset, metadata, sha256);
+    push_table(&mut out, "CONTENT_TYPES", dataset, Some(true));
+    push_table(&mut out, "INCOMPRESSIBLE_TYPES", dataset, Some(false));
+    push_media_types(&mut out, dataset);
+    push_extensions(&mut out, dataset);
+    out
+}
+
+/// Appends the public constants describing which dataset is embedded.
+fn push_provenance(out: &mut String, dataset: &Dataset, metadata: &Metadata, sha256: &str) {
+    out.push_str(&format!(
+        "/// The mime-db release the embedded dataset comes from, or `\"unreleased\"`\n\
+         /// for a snapshot taken between releases or not taken from a release.\n\
+         pub const DATASET_VERSION: &str = {:?};\n\
+         /// The jshttp/mime-db commit the embedded dataset is a verbatim copy of,\n\
+         /// or `None` if it is not a verbatim upstream copy.\n\
+         pub const DATASET_COMMIT: Option<&str> = {:?};\n\
+         /// The lowercase hex SHA-256 of the embedded `db.json`.\n\
+         pub const DATASET_SHA256: &str = {:?};\n\
+         /// The number of media types in the embedded dataset, including those\n\
+         /// without a `compressible` flag.\n\
+         pub const DATASET_ENTRIES: usize = {};\n",
+        metadata.version,
+        metadata.commit,
+        sha256,
+        dataset.0.len()
+    ));
+}
+
+/// Appends a sorted `static` array of the media types whose `compressible`
+/// field equals `compressible`.
+fn push_table(out: &mut String, name: &str, dataset: &Dataset, compressible: Option<bool>) {
+    let media_types: Vec<&str> = dataset
+        .0
+        .iter()
+        .filter(|(_, entry)| entry.compressible == compressible)
+        .map(|(media_type, _)| media_type.as_str())
+        .collect();
+
+    out.push_str(&format!(
+        "static {}: [&str; {}] = [\n",
+        name,
+        media_types.len()
+    ));
+    for media_type in media_types {
+        out.push_str(&format!("    {:?},\n", media_type));
+    }
+    out.push_str("];\n");
+}
+
+/// Appends a sorted `static` array with a `MediaTypeInfo` for every media
+/// type, and a perfect hash map from each media type to its index, so lookups
+/// cost one hash and one string comparison.
+fn push_media_types(out: &mut String, dataset: &Dataset) {
+    out.push_str(&format!(
+        "static MEDIA_TYPE_INFO: [MediaTypeInfo; {}] = [\n",
+        dataset.0.len()
+    ));
+    for (media_type, entry) in &dataset.0 {
+        let source = match entry.source.as_deref() {
+            Some("apache") => "Some(Source::Apache)",
+            Some("iana") => "Some(Source::Iana)",
+            Some("nginx") => "Some(Source::Nginx)",
+            _ => "None",
+        };
+        let compressibility = match entry.compressible {
+            Some(true) => "Yes",
+            Some(false) => "No",
+            None => "Unknown",
+        };
+        out.push_str(&format!(
+            "    MediaTypeInfo {{ essence: {:?}, source: {}, charset: {:?}, \
+             compressibility: Compressibility::{}, extensions: &{:?} }},\n",
+            media_type, source, entry.charset, compressibility, entry.extensions
+        ));
+    }
+    out.push_str("];\n");
+
+    let mut map = phf_codegen::Map::new();
+    for (index, (media_type, _)) in dataset.0.iter().enumerate() {
+        map.entry(media_type.as_str(), &index.to_string());
+    }
+    out.push_str(&format!(
+        "static MEDIA_TYPES: phf::Map<&str, usize> = {};\n",
+        map.build()
+    ));
+}
+
+/// Appends a `static` array of `(extension, media type)` pairs sorted by
+/// extension.
+///
+/// Extensions claimed by several types are resolved the same way the
+/// `mime-types` npm package does: prefer the type from the more authoritative
+/// source, and on a tie keep the earlier type only if it is `application/*`,
+/// otherwise take the later one. `application/octet-stream` always gives way.
+fn push_extensions(out: &mut String, dataset: &Dataset) {
+    fn rank(source: Option<&str>) -> usize {
+        match source {
+            Some("nginx") => 0,
+            Some("apache") => 1,
+            None => 2,
+            Some("iana") => 3,
+            Some(_) => 2,
+        }
+    }
+
+    let mut extensions: BTreeMap<&str, (&str, Option<&str>)> = BTreeMap::new();
+    for (media_type, entry) in &dataset.0 {
+        let source = entry.source.as_deref();
+        for extension in &entry.extensions {
+            if let Some(&(current, current_source)) = extensions.get(extension.as_str()) {
+                let (from, to) = (rank(current_source), rank(source));
+                if current != "application/octet-stream"
+                    && (from > to || (from == to && current.starts_with("application/")))
+                {
+                    continue;
+                }
+            }
+            extensions.insert(extension, (media_type, source));
+        }
+    }
+
+    out.push_str(&format!(
+        "static EXTENSIONS: [(&str, &str); {}] = [\n",
+        extensions.len()
+    ));
+    for (extension, (media_type, _)) in extensions {
+        out.push_str(&format!("    ({:?}, {:?}),\n", extension, media_type));
+    }
+    out.push_str("];\n");
+}
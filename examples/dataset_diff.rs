@@ -0,0 +1,229 @@
+//! Reports how the compressible flags differ between two mime-db snapshots,
+//! for reviewing dataset upgrades.
+//!
+//! ```sh
+//! # Embedded dataset against a newer db.json.
+//! cargo run --example dataset_diff -- new/db.json
+//! # Two db.json files, as JSON.
+//! cargo run --example dataset_diff -- --json old/db.json new/db.json
+//! ```
+//!
+//! Each line of the default output is tab-separated: `added`, `removed` or
+//! `changed`, the media type, and its flag (`yes`, `no` or `unknown` when
+//! mime-db has none), as `old -> new` for changes.
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::process;
+
+use compressible::{media_types, Compressibility, DATASET_COMMIT, DATASET_SHA256};
+use serde_json::{json, Value};
+
+/// Media types mapped to their compressible flag.
+type Snapshot = BTreeMap<String, Compressibility>;
+
+const USAGE: &str = "usage: dataset_diff [--json] [OLD] NEW";
+
+fn main() {
+    let mut json = false;
+    let mut files = Vec::new();
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => files.push(arg),
+        }
+    }
+
+    let result = match files.as_slice() {
+        [new] => load(new).map(|new| (embedded_name(), embedded(), new)),
+        [old, new] => load(old).and_then(|snapshot| Ok((old.clone(), snapshot, load(new)?))),
+        _ => Err(USAGE.to_owned()),
+    };
+    let (old_name, old, new) = match result {
+        Ok(snapshots) => snapshots,
+        Err(err) => {
+            eprintln!("dataset_diff: {}", err);
+            process::exit(2);
+        }
+    };
+    let new_name = files.last().expect("NEW is required");
+
+    let diff = diff(&old, &new);
+    if json {
+        let report = json!({
+            "old": old_name,
+            "new": new_name,
+            "added": diff.added.iter().map(|(media_type, flag)| {
+                json!({ "type": media_type, "compressible": to_json(*flag) })
+            }).collect::<Vec<_>>(),
+            "removed": diff.removed.iter().map(|(media_type, flag)| {
+                json!({ "type": media_type, "compressible": to_json(*flag) })
+            }).collect::<Vec<_>>(),
+            "changed": diff.changed.iter().map(|(media_type, old, new)| {
+                json!({ "type": media_type, "old": to_json(*old), "new": to_json(*new) })
+            }).collect::<Vec<_>>(),
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("report is valid JSON")
+        );
+    } else {
+        for (media_type, flag) in &diff.added {
+            println!("added\t{}\t{}", media_type, flag);
+        }
+        for (media_type, flag) in &diff.removed {
+            println!("removed\t{}\t{}", media_type, flag);
+        }
+        for (media_type, old, new) in &diff.changed {
+            println!("changed\t{}\t{} -> {}", media_type, old, new);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Diff<'a> {
+    added: Vec<(&'a str, Compressibility)>,
+    removed: Vec<(&'a str, Compressibility)>,
+    changed: Vec<(&'a str, Compressibility, Compressibility)>,
+}
+
+fn diff<'a>(old: &'a Snapshot, new: &'a Snapshot) -> Diff<'a> {
+    let mut diff = Diff::default();
+    for (media_type, &flag) in new {
+        match old.get(media_type) {
+            None => diff.added.push((media_type, flag)),
+            Some(&previous) if previous != flag => diff.changed.push((media_type, previous, flag)),
+            Some(_) => {}
+        }
+    }
+    for (media_type, &flag) in old {
+        if !new.contains_key(media_type) {
+            diff.removed.push((media_type, flag));
+        }
+    }
+    diff
+}
+
+/// Names the embedded dataset by its upstream commit, or by its SHA-256 when
+/// it is not a verbatim upstream copy.
+fn embedded_name() -> String {
+    match DATASET_COMMIT {
+        Some(commit) => format!("embedded@{}", commit),
+        None => format!("embedded@sha256:{}", DATASET_SHA256),
+    }
+}
+
+fn embedded() -> Snapshot {
+    media_types()
+        .map(|info| (info.essence.to_owned(), info.compressibility))
+        .collect()
+}
+
+fn load(path: &str) -> Result<Snapshot, String> {
+    let json = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let value: Value = serde_json::from_str(&json).map_err(|err| format!("{}: {}", path, err))?;
+    let entries = value
+        .as_object()
+        .ok_or_else(|| format!("{}: expected an object of media types", path))?;
+
+    Ok(entries
+        .iter()
+        .map(|(media_type, entry)| {
+            let flag = match entry.get("compressible").and_then(Value::as_bool) {
+                Some(true) => Compressibility::Yes,
+                Some(false) => Compressibility::No,
+                None => Compressibility::Unknown,
+            };
+            (media_type.to_ascii_lowercase(), flag)
+        })
+        .collect())
+}
+
+fn to_json(flag: Compressibility) -> Value {
+    match flag {
+        Compressibility::Yes => Value::Bool(true),
+        Compressibility::No => Value::Bool(false),
+        Compressibility::Unknown => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, Compressibility)]) -> Snapshot {
+        entries
+            .iter()
+            .map(|&(media_type, flag)| (media_type.to_owned(), flag))
+            .collect()
+    }
+
+    #[test]
+    fn diffs_snapshots() {
+        use Compressibility::*;
+
+        let old = snapshot(&[("a/kept", Yes), ("a/flipped", Unknown), ("a/removed", No)]);
+        let new = snapshot(&[("a/kept", Yes), ("a/flipped", Yes), ("a/added", Unknown)]);
+        let diff = diff(&old, &new);
+
+        assert_eq!(diff.added, [("a/added", Unknown)]);
+        assert_eq!(diff.removed, [("a/removed", No)]);
+        assert_eq!(diff.changed, [("a/flipped", Unknown, Yes)]);
+    }
+
+    #[test]
+    fn loads_db_json() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            file.path(),
+            r#"{
+                "text/CSS": { "compressible": true },
+                "image/png": { "compressible": false },
+                "text/x-null": { "compressible": null },
+                "text/x-missing": {}
+            }"#,
+        )
+        .unwrap();
+        let loaded = load(file.path().to_str().unwrap());
+
+        assert_eq!(
+            loaded.unwrap(),
+            snapshot(&[
+                ("text/css", Compressibility::Yes),
+                ("image/png", Compressibility::No),
+                ("text/x-null", Compressibility::Unknown),
+                ("text/x-missing", Compressibility::Unknown),
+            ])
+        );
+    }
+
+    #[test]
+    fn null_to_bool_is_a_change() {
+        let old = snapshot(&[("text/x-null", Compressibility::Unknown)]);
+        let new = snapshot(&[("text/x-null", Compressibility::No)]);
+        let diff = diff(&old, &new);
+
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(
+            diff.changed,
+            [("text/x-null", Compressibility::Unknown, Compressibility::No)]
+        );
+        assert_eq!(to_json(Compressibility::Unknown), Value::Null);
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert!(load("/nonexistent/db.json").is_err());
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), "[]").unwrap();
+        let loaded = load(file.path().to_str().unwrap());
+        assert!(loaded
+            .unwrap_err()
+            .ends_with("expected an object of media types"));
+    }
+}
@@ -0,0 +1,32 @@
+#!/bin/sh
+# Vendors jshttp/mime-db's db.json at COMMIT byte for byte and records its
+# provenance in data/db.meta.json.
+#
+#   scripts/update-dataset.sh COMMIT [VERSION]
+#
+# VERSION is the mime-db release COMMIT is tagged as, or `unreleased`.
+set -eu
+
+if [ $# -lt 1 ] || [ $# -gt 2 ]; then
+    echo "usage: $0 COMMIT [VERSION]" >&2
+    exit 2
+fi
+commit=$1
+version=${2:-unreleased}
+
+cd "$(dirname "$0")/.."
+curl --fail --silent --show-error --location \
+    --output data/db.json.tmp \
+    "https://raw.githubusercontent.com/jshttp/mime-db/$commit/db.json"
+mv data/db.json.tmp data/db.json
+
+sha256=$(sha256sum data/db.json | cut -d ' ' -f 1)
+cat > data/db.meta.json <<JSON
+{
+  "version": "$version",
+  "commit": "$commit",
+  "sha256": "$sha256"
+}
+JSON
+
+echo "data/db.json: mime-db $version at $commit, sha256 $sha256"
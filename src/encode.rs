@@ -0,0 +1,394 @@
+//! Compression encoders keyed by [`Encoding`].
+//!
+//! Each codec is behind a cargo feature of the same name (`gzip`, `deflate`,
+//! `brotli` and `zstd`). Asking for a codec whose feature is disabled returns
+//! an error of kind [`io::ErrorKind::Unsupported`]; `Encoding::Identity` is
+//! always available.
+//!
+//! ```
+//! # #[cfg(feature = "gzip")]
+//! # {
+//! use compressible::encode::{compress, Level};
+//! use compressible::Encoding;
+//!
+//! let body = "a body that repeats itself, ".repeat(10);
+//! let compressed = compress(Encoding::Gzip, Level::Default, body.as_bytes()).unwrap();
+//! assert!(compressed.len() < body.len());
+//! # }
+//! ```
+use std::io::{self, Write};
+
+use crate::Encoding;
+
+/// The largest zstd window, as a power of two, that the HTTP `zstd` content
+/// coding allows: RFC 9659 limits it to 8 MiB, and browsers reject frames
+/// asking for more.
+#[cfg(feature = "zstd")]
+const ZSTD_MAX_WINDOW_LOG: u32 = 23;
+
+/// Compression level, mapped onto each codec's own scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Level {
+    /// The fastest level: 1 for gzip, deflate, brotli and zstd.
+    Fastest,
+    /// A balanced level suited to compressing responses on the fly: 6 for
+    /// gzip and deflate, 5 for brotli and 3 for zstd.
+    #[default]
+    Default,
+    /// The smallest output: 9 for gzip and deflate, 11 for brotli and 22 for
+    /// zstd, with the zstd window capped at the 8 MiB HTTP allows.
+    Best,
+    /// A codec specific level, clamped to the codec's range.
+    Precise(u32),
+}
+
+impl Level {
+    #[cfg(any(feature = "gzip", feature = "deflate"))]
+    fn flate2(self) -> flate2::Compression {
+        match self {
+            Level::Fastest => flate2::Compression::fast(),
+            Level::Default => flate2::Compression::default(),
+            Level::Best => flate2::Compression::best(),
+            Level::Precise(level) => flate2::Compression::new(level.min(9)),
+        }
+    }
+
+    #[cfg(feature = "brotli")]
+    fn brotli(self) -> u32 {
+        match self {
+            Level::Fastest => 1,
+            Level::Default => 5,
+            Level::Best => 11,
+            Level::Precise(level) => level.min(11),
+        }
+    }
+
+    #[cfg(feature = "zstd")]
+    fn zstd(self) -> i32 {
+        let range = zstd::compression_level_range();
+        match self {
+            Level::Fastest => 1,
+            Level::Default => zstd::DEFAULT_COMPRESSION_LEVEL,
+            Level::Best => *range.end(),
+            Level::Precise(level) => (level.min(i32::MAX as u32) as i32).clamp(1, *range.end()),
+        }
+    }
+}
+
+/// Returns whether the codec for `encoding` is compiled in.
+pub(crate) fn is_enabled(encoding: Encoding) -> bool {
+    match encoding {
+        Encoding::Identity => true,
+        Encoding::Gzip => cfg!(feature = "gzip"),
+        Encoding::Deflate => cfg!(feature = "deflate"),
+        Encoding::Brotli => cfg!(feature = "brotli"),
+        Encoding::Zstd => cfg!(feature = "zstd"),
+    }
+}
+
+/// Compresses `data` in one go.
+pub fn compress(encoding: Encoding, level: Level, data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = Encoder::new(encoding, level, Vec::new())?;
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// A [`Write`] adapter that compresses everything written to it into `W`.
+///
+/// Call [`Encoder::finish`] once done to write the trailer of the compressed
+/// stream and get the inner writer back.
+pub struct Encoder<W: Write> {
+    inner: Inner<W>,
+}
+
+enum Inner<W: Write> {
+    Identity(W),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<W>),
+    #[cfg(feature = "deflate")]
+    Deflate(flate2::write::ZlibEncoder<W>),
+    #[cfg(feature = "brotli")]
+    Brotli(Box<brotli::CompressorWriter<Recorded<W>>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Encoder<'static, W>),
+}
+
+impl<W: Write> Encoder<W> {
+    /// Creates an encoder writing `encoding` compressed data into `writer`.
+    #[allow(unused_variables)]
+    pub fn new(encoding: Encoding, level: Level, writer: W) -> io::Result<Self> {
+        let inner = match encoding {
+            Encoding::Identity => Inner::Identity(writer),
+            #[cfg(feature = "gzip")]
+            Encoding::Gzip => Inner::Gzip(flate2::write::GzEncoder::new(writer, level.flate2())),
+            #[cfg(feature = "deflate")]
+            Encoding::Deflate => {
+                Inner::Deflate(flate2::write::ZlibEncoder::new(writer, level.flate2()))
+            }
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => Inner::Brotli(Box::new(brotli::CompressorWriter::new(
+                Recorded {
+                    inner: writer,
+                    error: None,
+                },
+                4096,
+                level.brotli(),
+                22,
+            ))),
+            #[cfg(feature = "zstd")]
+            Encoding::Zstd => {
+                let level = level.zstd();
+                let mut encoder = zstd::Encoder::new(writer, level)?;
+                // zstd's own parameters for levels 1 to 19 stay within the
+                // limit, the levels above default to windows of 32 MiB and up.
+                if level > 19 {
+                    encoder.window_log(ZSTD_MAX_WINDOW_LOG)?;
+                }
+                Inner::Zstd(encoder)
+            }
+            #[allow(unreachable_patterns)]
+            encoding => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("the `{}` encoder is not enabled", encoding),
+                ))
+            }
+        };
+        Ok(Encoder { inner })
+    }
+
+    /// Finishes the compressed stream and returns the inner writer.
+    pub fn finish(self) -> io::Result<W> {
+        match self.inner {
+            Inner::Identity(writer) => Ok(writer),
+            #[cfg(feature = "gzip")]
+            Inner::Gzip(encoder) => encoder.finish(),
+            #[cfg(feature = "deflate")]
+            Inner::Deflate(encoder) => encoder.finish(),
+            #[cfg(feature = "brotli")]
+            Inner::Brotli(encoder) => {
+                // `into_inner` writes the end of the stream but drops any
+                // error doing so, so it is recorded on the way through.
+                let recorded = encoder.into_inner();
+                match recorded.error {
+                    Some(err) => Err(err),
+                    None => Ok(recorded.inner),
+                }
+            }
+            #[cfg(feature = "zstd")]
+            Inner::Zstd(encoder) => encoder.finish(),
+        }
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    ///
+    /// Compressed output is only written to it as the codec produces it; call
+    /// [`Write::flush`] first to make everything written so far available.
+    /// Writing to it directly corrupts the compressed stream.
+    pub fn get_mut(&mut self) -> &mut W {
+        match &mut self.inner {
+            Inner::Identity(writer) => writer,
+            #[cfg(feature = "gzip")]
+            Inner::Gzip(encoder) => encoder.get_mut(),
+            #[cfg(feature = "deflate")]
+            Inner::Deflate(encoder) => encoder.get_mut(),
+            #[cfg(feature = "brotli")]
+            Inner::Brotli(encoder) => &mut encoder.get_mut().inner,
+            #[cfg(feature = "zstd")]
+            Inner::Zstd(encoder) => encoder.get_mut(),
+        }
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match &mut self.inner {
+            Inner::Identity(writer) => writer,
+            #[cfg(feature = "gzip")]
+            Inner::Gzip(encoder) => encoder,
+            #[cfg(feature = "deflate")]
+            Inner::Deflate(encoder) => encoder,
+            #[cfg(feature = "brotli")]
+            Inner::Brotli(encoder) => encoder,
+            #[cfg(feature = "zstd")]
+            Inner::Zstd(encoder) => encoder,
+        }
+    }
+}
+
+/// Keeps the first error the inner writer returns, for codecs that discard
+/// it.
+#[cfg(feature = "brotli")]
+struct Recorded<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "brotli")]
+impl<W: Write> Recorded<W> {
+    fn record<T>(&mut self, result: io::Result<T>) -> io::Result<T> {
+        result.map_err(|err| {
+            let copy = io::Error::new(err.kind(), err.to_string());
+            if err.kind() != io::ErrorKind::Interrupted && self.error.is_none() {
+                self.error = Some(err);
+            }
+            copy
+        })
+    }
+}
+
+#[cfg(feature = "brotli")]
+impl<W: Write> Write for Recorded<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let result = self.inner.write(buf);
+        self.record(result)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.inner.flush();
+        self.record(result)
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer().flush()
+    }
+}
+
+/// Returns the window size a zstd frame header asks the decoder for.
+#[cfg(all(test, feature = "zstd"))]
+pub(crate) fn zstd_window_size(frame: &[u8]) -> u64 {
+    assert_eq!(frame[..4], [0x28, 0xb5, 0x2f, 0xfd], "zstd magic number");
+    let descriptor = frame[4];
+    assert_eq!(
+        descriptor & 0x20,
+        0,
+        "streamed frames are not single segment"
+    );
+
+    let window = frame[5];
+    let base = 1u64 << (10 + (window >> 3));
+    base + base / 8 * u64::from(window & 0x07)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(any(feature = "gzip", feature = "deflate", feature = "brotli"))]
+    use std::io::Read;
+
+    use super::*;
+
+    const BODY: &[u8] =
+        br#"{"items":[{"id":1,"name":"compressible"},{"id":2,"name":"compressible"}]}"#;
+
+    fn decompress(encoding: Encoding, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        match encoding {
+            Encoding::Identity => out.extend_from_slice(data),
+            #[cfg(feature = "gzip")]
+            Encoding::Gzip => {
+                flate2::read::GzDecoder::new(data)
+                    .read_to_end(&mut out)
+                    .unwrap();
+            }
+            #[cfg(feature = "deflate")]
+            Encoding::Deflate => {
+                flate2::read::ZlibDecoder::new(data)
+                    .read_to_end(&mut out)
+                    .unwrap();
+            }
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => {
+                brotli::Decompressor::new(data, 4096)
+                    .read_to_end(&mut out)
+                    .unwrap();
+            }
+            #[cfg(feature = "zstd")]
+            Encoding::Zstd => out = zstd::decode_all(data).unwrap(),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        }
+        out
+    }
+
+    #[test]
+    fn round_trips_enabled_encodings() {
+        for &encoding in &Encoding::ALL {
+            for &level in &[
+                Level::Fastest,
+                Level::Default,
+                Level::Best,
+                Level::Precise(100),
+            ] {
+                match compress(encoding, level, BODY) {
+                    Ok(compressed) => assert_eq!(decompress(encoding, &compressed), BODY),
+                    Err(err) => assert_eq!(err.kind(), io::ErrorKind::Unsupported),
+                }
+                assert_eq!(
+                    compress(encoding, level, BODY).is_ok(),
+                    is_enabled(encoding)
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn brotli_finish_reports_write_errors() {
+        /// Accepts `capacity` bytes, then fails.
+        struct Full {
+            capacity: usize,
+        }
+
+        impl Write for Full {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                if self.capacity == 0 {
+                    return Err(io::Error::new(io::ErrorKind::WriteZero, "disk full"));
+                }
+                let written = buf.len().min(self.capacity);
+                self.capacity -= written;
+                Ok(written)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        // brotli buffers the whole body, so only finishing writes to `Full`.
+        let mut encoder =
+            Encoder::new(Encoding::Brotli, Level::Default, Full { capacity: 0 }).unwrap();
+        encoder.write_all(BODY).unwrap();
+        let err = encoder.finish().err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+        assert_eq!(err.to_string(), "disk full");
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_windows_fit_http() {
+        let mut levels = vec![Level::Fastest, Level::Default, Level::Best];
+        levels.extend((1..=22).map(Level::Precise));
+        for level in levels {
+            let compressed = compress(Encoding::Zstd, level, BODY).unwrap();
+            assert!(
+                zstd_window_size(&compressed) <= 8 << 20,
+                "{:?} asks for a {} byte window",
+                level,
+                zstd_window_size(&compressed)
+            );
+            assert_eq!(decompress(Encoding::Zstd, &compressed), BODY);
+        }
+    }
+
+    #[test]
+    fn streams_into_writer() {
+        let mut encoder = Encoder::new(Encoding::Identity, Level::Default, Vec::new()).unwrap();
+        encoder.write_all(b"hello ").unwrap();
+        encoder.write_all(b"world").unwrap();
+        assert_eq!(encoder.finish().unwrap(), b"hello world");
+    }
+}
@@ -0,0 +1,261 @@
+//! An allocation-free parser for the `type/subtype` essence of a content
+//! type.
+//!
+//! Only the essence is validated, following the same token rules as the
+//! `mime` crate. Parameters after `;` are ignored rather than parsed.
+use core::cmp::Ordering;
+use core::fmt;
+use core::str;
+
+/// Longest essence accepted: RFC 6838 limits type and subtype names to 127
+/// characters each.
+const MAX_LEN: usize = 127 + 1 + 127;
+
+/// The lowercased `type/subtype` of a content type, stored inline.
+///
+/// This is the parser every lookup in the crate goes through, so callers that
+/// need to agree with them, e.g. about what counts as a content type, can use
+/// it directly.
+///
+/// ```
+/// use compressible::Essence;
+///
+/// let essence = Essence::parse("Image/SVG+XML; charset").unwrap();
+/// assert_eq!(essence.as_str(), "image/svg+xml");
+/// assert_eq!(essence.suffix(), Some("xml"));
+/// assert!(Essence::parse("assets/app.js ").is_none());
+/// ```
+#[derive(Clone)]
+pub struct Essence {
+    buf: [u8; MAX_LEN],
+    len: usize,
+    slash: usize,
+}
+
+impl Essence {
+    /// Parses the essence of a content type, returning `None` if it is not a
+    /// valid `type/subtype` followed by nothing or by `;` and parameters.
+    pub fn parse(content_type: &str) -> Option<Essence> {
+        let bytes = content_type.as_bytes();
+        let end = bytes.iter().position(|&b| b == b';').unwrap_or(bytes.len());
+        let essence = &bytes[..end];
+        let slash = essence.iter().position(|&b| b == b'/')?;
+
+        if essence.len() > MAX_LEN
+            || slash == 0
+            || slash + 1 == essence.len()
+            || !essence[..slash].iter().all(|&b| is_token(b))
+            || !essence[slash + 1..].iter().all(|&b| is_token(b))
+        {
+            return None;
+        }
+
+        let mut buf = [0; MAX_LEN];
+        for (dst, src) in buf.iter_mut().zip(essence) {
+            *dst = src.to_ascii_lowercase();
+        }
+        Some(Essence {
+            buf,
+            len: essence.len(),
+            slash,
+        })
+    }
+
+    /// Returns the lowercased `type/subtype`.
+    pub fn as_str(&self) -> &str {
+        // Only ASCII token bytes are ever copied into the buffer.
+        str::from_utf8(&self.buf[..self.len]).expect("essence is ASCII")
+    }
+
+    /// Returns the top-level type, e.g. `text`.
+    pub fn type_(&self) -> &str {
+        &self.as_str()[..self.slash]
+    }
+
+    /// Returns the subtype, e.g. `html`.
+    pub fn subtype(&self) -> &str {
+        &self.as_str()[self.slash + 1..]
+    }
+
+    /// Returns the structured syntax suffix after the last `+` of the
+    /// subtype, e.g. `json` for `application/vnd.api+json`.
+    pub fn suffix(&self) -> Option<&str> {
+        suffix(self.subtype())
+    }
+}
+
+impl fmt::Debug for Essence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Essence").field(&self.as_str()).finish()
+    }
+}
+
+impl fmt::Display for Essence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Returns the structured syntax suffix of a subtype. A leading `+` does not
+/// start a suffix.
+pub(crate) fn suffix(subtype: &str) -> Option<&str> {
+    match subtype.rfind('+') {
+        Some(0) | None => None,
+        Some(plus) => Some(&subtype[plus + 1..]),
+    }
+}
+
+/// Compares two strings ASCII case-insensitively without allocating.
+///
+/// Tables are sorted by their lowercase keys, so this can be handed to
+/// `binary_search_by` with a key of any case. It is a `const fn` so that the
+/// ordering of hand-written tables can be checked at compile time.
+pub(crate) const fn cmp_ignore_ascii_case(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut i = 0;
+    while i < a.len() && i < b.len() {
+        let (x, y) = (a[i].to_ascii_lowercase(), b[i].to_ascii_lowercase());
+        if x != y {
+            return if x < y {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            };
+        }
+        i += 1;
+    }
+    if a.len() < b.len() {
+        Ordering::Less
+    } else if a.len() > b.len() {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// RFC 7230 `tchar`.
+fn is_token(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_essence() {
+        let essence = Essence::parse("Application/Vnd.API+JSON; charset=utf-8").unwrap();
+        assert_eq!(essence.as_str(), "application/vnd.api+json");
+        assert_eq!(essence.type_(), "application");
+        assert_eq!(essence.subtype(), "vnd.api+json");
+        assert_eq!(essence.suffix(), Some("json"));
+
+        assert_eq!(Essence::parse("text/+json").unwrap().suffix(), None);
+        assert_eq!(Essence::parse("text/html;").unwrap().as_str(), "text/html");
+    }
+
+    #[test]
+    fn compares_ignoring_ascii_case() {
+        assert_eq!(cmp_ignore_ascii_case("json", "JSON"), Ordering::Equal);
+        assert_eq!(cmp_ignore_ascii_case("json", "JSON-seq"), Ordering::Less);
+        assert_eq!(cmp_ignore_ascii_case("xml", "Json"), Ordering::Greater);
+        assert_eq!(cmp_ignore_ascii_case("", ""), Ordering::Equal);
+        // Non-ASCII bytes are compared as they are.
+        assert_eq!(cmp_ignore_ascii_case("\u{e9}", "\u{c9}"), Ordering::Greater);
+    }
+
+    #[test]
+    fn ignores_parameters() {
+        // `mime` rejects the malformed parameter, the essence is still valid.
+        assert_eq!(
+            Essence::parse("text/html; charset").unwrap().as_str(),
+            "text/html"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_essences() {
+        let long = format!("text/{}", "a".repeat(MAX_LEN));
+        for input in &[
+            "",
+            "text",
+            "text/",
+            "/html",
+            " text/html",
+            "text/html ",
+            "text /html",
+            "text/ html",
+            "text/html ;a=b",
+            "text/html/x",
+            "text/h\u{e9}",
+            "text/html,text/css",
+            long.as_str(),
+        ] {
+            assert!(Essence::parse(input).is_none(), "{:?}", input);
+        }
+    }
+
+    /// The essence parser and `mime` must agree on everything the dataset
+    /// contains and on inputs that trip up hand-written parsers.
+    #[cfg(feature = "mime")]
+    #[test]
+    fn agrees_with_mime() {
+        use crate::{CONTENT_TYPES, INCOMPRESSIBLE_TYPES};
+
+        fn check(input: &str) {
+            let expected = input.parse::<mime::Mime>().ok();
+            let actual = Essence::parse(input);
+            assert_eq!(
+                actual.as_ref().map(Essence::as_str),
+                expected.as_ref().map(|mime| mime.essence_str()),
+                "{:?}",
+                input
+            );
+            assert_eq!(
+                actual.as_ref().and_then(Essence::suffix),
+                expected
+                    .as_ref()
+                    .and_then(|mime| mime.suffix())
+                    .map(|suffix| suffix.as_str()),
+                "{:?}",
+                input
+            );
+        }
+
+        for media_type in CONTENT_TYPES.iter().chain(INCOMPRESSIBLE_TYPES.iter()) {
+            check(media_type);
+            check(&media_type.to_ascii_uppercase());
+            check(&format!("{}; charset=utf-8", media_type));
+            check(&format!("{};q=0.5;level=1", media_type));
+        }
+
+        for input in &[
+            "",
+            "text",
+            "/html",
+            " text/html",
+            "\ttext/html",
+            "text/html ",
+            "text/html\n",
+            "text /html",
+            "text/ html",
+            "text/html ;charset=utf-8",
+            "text/html;",
+            "text/html; ",
+            "text/html;a=\"b;c\"",
+            "text/html/x",
+            "text/h\u{e9}",
+            "text/x{y",
+            "text/x@y",
+            "text/*",
+            "*/*",
+            "-text/-x",
+            "text/+json",
+            "application/vnd.a+json+xml",
+            "text/html,text/css",
+            "x-shader/x-vertex",
+        ] {
+            check(input);
+        }
+    }
+}
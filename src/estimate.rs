@@ -0,0 +1,219 @@
+//! Data driven compressibility estimates for payloads of unknown type.
+use crate::Compressibility;
+
+/// Options for [`estimate_compressibility`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EstimateOptions {
+    /// How many leading bytes of the payload to inspect. Defaults to 64 KiB.
+    pub sample_size: usize,
+    /// Whether to run a fast LZ77 trial compression of the sample in addition
+    /// to measuring its byte entropy. Catches repetition that entropy alone
+    /// misses, at the cost of a single pass over the sample. Defaults to
+    /// `true`.
+    pub trial_compression: bool,
+    /// The estimated fraction of bytes saved above which compression is
+    /// recommended. Defaults to `0.1`, i.e. 10%.
+    pub min_savings: f64,
+}
+
+impl Default for EstimateOptions {
+    fn default() -> Self {
+        EstimateOptions {
+            sample_size: 64 * 1024,
+            trial_compression: true,
+            min_savings: 0.1,
+        }
+    }
+}
+
+/// The result of [`estimate_compressibility`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// Number of bytes inspected.
+    pub sampled: usize,
+    /// Shannon entropy of the sampled bytes, in bits per byte (`0.0..=8.0`).
+    pub entropy: f64,
+    /// Estimated compressed size as a fraction of the original size.
+    pub ratio: f64,
+    /// `Compressibility::Yes` if the estimated savings reach
+    /// [`EstimateOptions::min_savings`], `Compressibility::No` otherwise, and
+    /// `Compressibility::Unknown` for an empty payload.
+    pub recommendation: Compressibility,
+}
+
+/// Estimates how well `data` compresses by sampling its first
+/// [`EstimateOptions::sample_size`] bytes.
+///
+/// The estimate is rough and meant for deciding whether compression is worth
+/// the CPU, e.g. per blob in an object store, not for predicting exact sizes.
+///
+/// ```
+/// use compressible::{estimate_compressibility, Compressibility, EstimateOptions};
+///
+/// let log = "GET /index.html 200\n".repeat(100);
+/// let estimate = estimate_compressibility(log.as_bytes(), EstimateOptions::default());
+/// assert_eq!(estimate.recommendation, Compressibility::Yes);
+/// assert!(estimate.ratio < 0.5);
+/// ```
+pub fn estimate_compressibility(data: &[u8], options: EstimateOptions) -> Estimate {
+    let sample = &data[..data.len().min(options.sample_size)];
+    if sample.is_empty() {
+        return Estimate {
+            sampled: 0,
+            entropy: 0.0,
+            ratio: 1.0,
+            recommendation: Compressibility::Unknown,
+        };
+    }
+
+    let entropy = entropy(sample);
+    let ratio = if options.trial_compression {
+        trial_ratio(sample, entropy)
+    } else {
+        entropy / 8.0
+    };
+
+    let recommendation = if 1.0 - ratio >= options.min_savings {
+        Compressibility::Yes
+    } else {
+        Compressibility::No
+    };
+
+    Estimate {
+        sampled: sample.len(),
+        entropy,
+        ratio,
+        recommendation,
+    }
+}
+
+fn entropy(sample: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &byte in sample {
+        counts[byte as usize] += 1;
+    }
+
+    let len = sample.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Runs a greedy LZ4 style match finder over the sample and returns the
+/// estimated compressed size as a fraction of the sample size.
+///
+/// Literals are costed at the sample's entropy, matches at three bytes each,
+/// approximating an entropy coded LZ77 codec such as deflate.
+fn trial_ratio(sample: &[u8], entropy: f64) -> f64 {
+    const MIN_MATCH: usize = 4;
+    const HASH_BITS: u32 = 12;
+    const MATCH_COST: f64 = 3.0;
+
+    let hash = |bytes: &[u8]| {
+        let word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        (word.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+    };
+
+    let mut table = vec![usize::MAX; 1 << HASH_BITS];
+    let mut literals = 0;
+    let mut matches = 0;
+    let mut position = 0;
+
+    while position + MIN_MATCH <= sample.len() {
+        let slot = hash(&sample[position..]);
+        let candidate = table[slot];
+        table[slot] = position;
+
+        if candidate != usize::MAX
+            && sample[candidate..candidate + MIN_MATCH] == sample[position..position + MIN_MATCH]
+        {
+            let len = sample[position..]
+                .iter()
+                .zip(&sample[candidate..])
+                .take_while(|(a, b)| a == b)
+                .count();
+            matches += 1;
+            position += len;
+        } else {
+            literals += 1;
+            position += 1;
+        }
+    }
+    literals += sample.len() - position;
+
+    let size = literals as f64 * entropy / 8.0 + matches as f64 * MATCH_COST;
+    (size / sample.len() as f64).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo random bytes, standing in for encrypted or
+    /// already compressed data.
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state: u32 = 0x9E37_79B9;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn random_data_is_not_worth_compressing() {
+        let estimate = estimate_compressibility(&noise(100_000), EstimateOptions::default());
+
+        assert_eq!(estimate.sampled, 64 * 1024);
+        assert!(estimate.entropy > 7.9);
+        assert!(estimate.ratio > 0.95);
+        assert_eq!(estimate.recommendation, Compressibility::No);
+    }
+
+    #[test]
+    fn text_is_worth_compressing() {
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(50);
+        let options = EstimateOptions {
+            trial_compression: false,
+            ..EstimateOptions::default()
+        };
+        let entropy_only = estimate_compressibility(text.as_bytes(), options);
+        let trial = estimate_compressibility(text.as_bytes(), EstimateOptions::default());
+
+        assert_eq!(entropy_only.recommendation, Compressibility::Yes);
+        assert_eq!(trial.recommendation, Compressibility::Yes);
+        assert!(trial.ratio < entropy_only.ratio);
+    }
+
+    #[test]
+    fn repetition_with_high_entropy() {
+        let data = noise(1024).repeat(8);
+        let options = EstimateOptions {
+            trial_compression: false,
+            ..EstimateOptions::default()
+        };
+
+        assert_eq!(
+            estimate_compressibility(&data, options).recommendation,
+            Compressibility::No
+        );
+        assert_eq!(
+            estimate_compressibility(&data, EstimateOptions::default()).recommendation,
+            Compressibility::Yes
+        );
+    }
+
+    #[test]
+    fn empty_is_unknown() {
+        let estimate = estimate_compressibility(b"", EstimateOptions::default());
+        assert_eq!(estimate.recommendation, Compressibility::Unknown);
+    }
+}
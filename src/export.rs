@@ -0,0 +1,300 @@
+//! Rendering the compressible set as web server configuration.
+//!
+//! ```
+//! use compressible::export::{render, Format};
+//!
+//! assert_eq!(
+//!     render(Format::Apache, ["application/json", "text/css"]),
+//!     "AddOutputFilterByType DEFLATE application/json\n\
+//!      AddOutputFilterByType DEFLATE text/css\n"
+//! );
+//! ```
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::compressible_types;
+
+/// A configuration format to render media types in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// An nginx `gzip_types` directive.
+    NginxGzip,
+    /// An nginx `brotli_types` directive, for the ngx_brotli module.
+    NginxBrotli,
+    /// Apache `AddOutputFilterByType DEFLATE` lines, for mod_deflate.
+    Apache,
+    /// A Caddyfile `encode` directive with a `Content-Type` matcher.
+    Caddy,
+    /// A HAProxy `compression type` directive.
+    HaProxy,
+    /// The `content_type` list of an Envoy compressor filter, as YAML.
+    Envoy,
+}
+
+impl Format {
+    /// Every format.
+    pub const ALL: [Format; 6] = [
+        Format::NginxGzip,
+        Format::NginxBrotli,
+        Format::Apache,
+        Format::Caddy,
+        Format::HaProxy,
+        Format::Envoy,
+    ];
+
+    /// Returns the name used for this format on the command line.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Format::NginxGzip => "nginx-gzip",
+            Format::NginxBrotli => "nginx-brotli",
+            Format::Apache => "apache",
+            Format::Caddy => "caddy",
+            Format::HaProxy => "haproxy",
+            Format::Envoy => "envoy",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Format {
+    type Err = ParseFormatError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Format::ALL
+            .iter()
+            .copied()
+            .find(|format| format.as_str().eq_ignore_ascii_case(name.trim()))
+            .ok_or(ParseFormatError)
+    }
+}
+
+/// Error returned when parsing an unknown export format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFormatError;
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown export format")
+    }
+}
+
+impl Error for ParseFormatError {}
+
+/// Renders every compressible media type in the dataset in `format`.
+pub fn export(format: Format) -> String {
+    render(format, compressible_types())
+}
+
+/// The longest `compression type` line rendered for HAProxy, which rejects
+/// lines over 2 KiB.
+const HAPROXY_LINE_LEN: usize = 1024;
+
+/// The most media types on a `compression type` line, as HAProxy accepts at
+/// most 64 words per line.
+const HAPROXY_LINE_TYPES: usize = 60;
+
+/// Renders the provided media types in `format`.
+///
+/// nginx always compresses `text/html` and warns about it being listed, so it
+/// is left out of the nginx directives. When no media types are left to
+/// render, the output is empty rather than a directive without arguments,
+/// which servers either reject or treat as their built-in defaults.
+///
+/// HAProxy types are spread over as many `compression type` lines as needed
+/// to stay within its line limits.
+pub fn render<'a, I>(format: Format, types: I) -> String
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut types = types
+        .into_iter()
+        .filter(|media_type| {
+            !matches!(format, Format::NginxGzip | Format::NginxBrotli) || *media_type != "text/html"
+        })
+        .peekable();
+    let mut out = String::new();
+    if types.peek().is_none() {
+        return out;
+    }
+
+    match format {
+        Format::NginxGzip | Format::NginxBrotli => {
+            out.push_str(match format {
+                Format::NginxGzip => "gzip_types",
+                _ => "brotli_types",
+            });
+            for media_type in types {
+                out.push_str("\n    ");
+                out.push_str(media_type);
+            }
+            out.push_str(";\n");
+        }
+        Format::Apache => {
+            for media_type in types {
+                out.push_str("AddOutputFilterByType DEFLATE ");
+                out.push_str(media_type);
+                out.push('\n');
+            }
+        }
+        Format::Caddy => {
+            out.push_str("encode zstd gzip {\n\tmatch {\n");
+            for media_type in types {
+                // The trailing `*` lets the matcher accept parameters.
+                out.push_str("\t\theader Content-Type ");
+                out.push_str(media_type);
+                out.push_str("*\n");
+            }
+            out.push_str("\t}\n}\n");
+        }
+        Format::HaProxy => {
+            const DIRECTIVE: &str = "compression type";
+            let mut line_start = 0;
+            let mut line_types = 0;
+            for media_type in types {
+                let line_len = out.len() - line_start;
+                if line_types == 0
+                    || line_types == HAPROXY_LINE_TYPES
+                    || line_len + 1 + media_type.len() > HAPROXY_LINE_LEN
+                {
+                    if line_types > 0 {
+                        out.push('\n');
+                    }
+                    line_start = out.len();
+                    line_types = 0;
+                    out.push_str(DIRECTIVE);
+                }
+                out.push(' ');
+                out.push_str(media_type);
+                line_types += 1;
+            }
+            out.push('\n');
+        }
+        Format::Envoy => {
+            out.push_str("content_type:\n");
+            for media_type in types {
+                out.push_str("- ");
+                out.push_str(media_type);
+                out.push('\n');
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TYPES: [&str; 3] = ["application/json", "image/svg+xml", "text/html"];
+
+    #[test]
+    fn nginx() {
+        assert_eq!(
+            render(Format::NginxGzip, TYPES),
+            "gzip_types\n    application/json\n    image/svg+xml;\n"
+        );
+        assert_eq!(
+            render(Format::NginxBrotli, TYPES),
+            "brotli_types\n    application/json\n    image/svg+xml;\n"
+        );
+    }
+
+    #[test]
+    fn apache() {
+        assert_eq!(
+            render(Format::Apache, TYPES),
+            "AddOutputFilterByType DEFLATE application/json\n\
+             AddOutputFilterByType DEFLATE image/svg+xml\n\
+             AddOutputFilterByType DEFLATE text/html\n"
+        );
+    }
+
+    #[test]
+    fn caddy() {
+        assert_eq!(
+            render(Format::Caddy, TYPES),
+            "encode zstd gzip {\n\
+             \tmatch {\n\
+             \t\theader Content-Type application/json*\n\
+             \t\theader Content-Type image/svg+xml*\n\
+             \t\theader Content-Type text/html*\n\
+             \t}\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn haproxy() {
+        assert_eq!(
+            render(Format::HaProxy, TYPES),
+            "compression type application/json image/svg+xml text/html\n"
+        );
+    }
+
+    #[test]
+    fn haproxy_splits_long_lists() {
+        let haproxy = export(Format::HaProxy);
+        let lines: Vec<_> = haproxy.lines().collect();
+        assert!(lines.len() > 1);
+        for line in &lines {
+            assert!(line.len() <= HAPROXY_LINE_LEN, "{}", line);
+            assert!(
+                line.split(' ').count() <= HAPROXY_LINE_TYPES + 2,
+                "{}",
+                line
+            );
+        }
+        let types: Vec<_> = lines
+            .iter()
+            .flat_map(|line| line.strip_prefix("compression type ").unwrap().split(' '))
+            .collect();
+        assert!(types.iter().copied().eq(compressible_types()));
+    }
+
+    #[test]
+    fn renders_nothing_for_no_types() {
+        for &format in &Format::ALL {
+            assert_eq!(render(format, []), "", "{}", format);
+        }
+        assert_eq!(render(Format::NginxGzip, ["text/html"]), "");
+        assert_eq!(
+            render(Format::Apache, ["text/html"]),
+            "AddOutputFilterByType DEFLATE text/html\n"
+        );
+    }
+
+    #[test]
+    fn envoy() {
+        assert_eq!(
+            render(Format::Envoy, TYPES),
+            "content_type:\n- application/json\n- image/svg+xml\n- text/html\n"
+        );
+    }
+
+    #[test]
+    fn exports_the_whole_dataset() {
+        let apache = export(Format::Apache);
+        assert_eq!(apache.lines().count(), compressible_types().count());
+        assert!(apache.contains("AddOutputFilterByType DEFLATE text/css\n"));
+
+        let nginx = export(Format::NginxGzip);
+        assert!(!nginx.contains("text/html\n"));
+        assert!(nginx.contains("\n    text/css\n"));
+    }
+
+    #[test]
+    fn parses_format_names() {
+        for format in Format::ALL.iter() {
+            assert_eq!(format.as_str().parse(), Ok(*format));
+        }
+        assert_eq!("HAProxy".parse(), Ok(Format::HaProxy));
+        assert_eq!("lighttpd".parse::<Format>(), Err(ParseFormatError));
+    }
+}
@@ -0,0 +1,105 @@
+//! Lookups keyed by file extension, for callers that know a file path rather
+//! than a content type.
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use crate::essence::cmp_ignore_ascii_case;
+use crate::{essence_compressibility, Compressibility, EXTENSIONS};
+
+/// Returns the media type mime-db associates with a file extension.
+///
+/// The extension is matched case-insensitively and may include a leading
+/// dot.
+///
+/// ```
+/// use compressible::extension_to_mime;
+///
+/// assert_eq!(extension_to_mime("css"), Some("text/css"));
+/// assert_eq!(extension_to_mime(".JPG"), Some("image/jpeg"));
+/// assert_eq!(extension_to_mime("unheard-of"), None);
+/// ```
+pub fn extension_to_mime(extension: &str) -> Option<&'static str> {
+    let extension = extension.strip_prefix('.').unwrap_or(extension);
+
+    EXTENSIONS
+        .binary_search_by(|(candidate, _)| cmp_ignore_ascii_case(candidate, extension))
+        .ok()
+        .map(|index| EXTENSIONS[index].1)
+}
+
+/// Returns the [`Compressibility`] of the media type associated with a file
+/// extension, or `Compressibility::Unknown` if the extension is not known.
+pub fn extension_compressibility(extension: &str) -> Compressibility {
+    extension_to_mime(extension)
+        .map(essence_compressibility)
+        .unwrap_or(Compressibility::Unknown)
+}
+
+/// Returns `bool` indicating whether files with the provided extension are
+/// compressible.
+///
+/// ```
+/// use compressible::is_extension_compressible;
+///
+/// assert_eq!(is_extension_compressible("css"), true);
+/// assert_eq!(is_extension_compressible("png"), false);
+/// ```
+pub fn is_extension_compressible(extension: &str) -> bool {
+    extension_compressibility(extension) == Compressibility::Yes
+}
+
+/// Returns `bool` indicating whether the file at `path` is compressible,
+/// judging by its extension. The file itself is not accessed.
+///
+/// ```
+/// use compressible::is_path_compressible;
+///
+/// assert_eq!(is_path_compressible("assets/app.min.js"), true);
+/// assert_eq!(is_path_compressible("assets/logo.png"), false);
+/// assert_eq!(is_path_compressible("Makefile"), false);
+/// ```
+#[cfg(feature = "std")]
+pub fn is_path_compressible<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(is_extension_compressible)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extensions_are_sorted() {
+        assert!(EXTENSIONS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn conflicting_extensions_prefer_authoritative_types() {
+        assert_eq!(extension_to_mime("xml"), Some("application/xml"));
+        assert_eq!(extension_to_mime("js"), Some("application/javascript"));
+        assert_eq!(extension_to_mime("wav"), Some("audio/wave"));
+        assert_eq!(extension_to_mime("ico"), Some("image/vnd.microsoft.icon"));
+        assert_eq!(
+            extension_to_mime("exe"),
+            Some("application/x-msdos-program")
+        );
+    }
+
+    #[test]
+    fn extension_lookups() {
+        assert_eq!(extension_compressibility("gif"), Compressibility::No);
+        assert_eq!(extension_compressibility("webp"), Compressibility::Unknown);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn path_lookups() {
+        assert!(is_path_compressible(Path::new("/srv/www/index.HTML")));
+        assert!(is_path_compressible("fonts/icons.svg"));
+        assert!(!is_path_compressible("photo.jpeg"));
+        assert!(!is_path_compressible("archive.tar.gz"));
+        assert!(!is_path_compressible("no-extension"));
+    }
+}
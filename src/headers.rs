@@ -0,0 +1,113 @@
+//! Deciding whether to compress a response from its headers.
+use http::header::{HeaderMap, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_TYPE};
+
+use crate::is_compressible;
+
+/// Returns `bool` indicating whether a response with the provided headers
+/// should be compressed.
+///
+/// A response is not compressed when it
+///
+/// - already has a `Content-Encoding` other than `identity`,
+/// - carries a `Cache-Control: no-transform` directive, or
+/// - has a missing or unparsable `Content-Type`.
+///
+/// Otherwise the `Content-Type` is checked with [`is_compressible`].
+///
+/// ```
+/// use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+/// use compressible::should_compress_response;
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/html; charset=utf-8"));
+///
+/// assert_eq!(should_compress_response(&headers), true);
+/// ```
+pub fn should_compress_response(headers: &HeaderMap) -> bool {
+    if is_encoded(headers) || has_no_transform(headers) {
+        return false;
+    }
+
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(is_compressible)
+}
+
+fn is_encoded(headers: &HeaderMap) -> bool {
+    headers.get_all(CONTENT_ENCODING).iter().any(|value| {
+        value.to_str().map_or(true, |value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .any(|coding| !coding.is_empty() && !coding.eq_ignore_ascii_case("identity"))
+        })
+    })
+}
+
+fn has_no_transform(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"))
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::{HeaderValue, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_TYPE};
+
+    use super::*;
+
+    fn headers(pairs: &[(http::header::HeaderName, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn uses_content_type() {
+        assert!(should_compress_response(&headers(&[(
+            CONTENT_TYPE,
+            "application/json"
+        )])));
+        assert!(!should_compress_response(&headers(&[(
+            CONTENT_TYPE,
+            "image/png"
+        )])));
+        assert!(!should_compress_response(&headers(&[(CONTENT_TYPE, "")])));
+        assert!(!should_compress_response(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn skips_encoded_responses() {
+        assert!(!should_compress_response(&headers(&[
+            (CONTENT_TYPE, "text/css"),
+            (CONTENT_ENCODING, "gzip"),
+        ])));
+        assert!(should_compress_response(&headers(&[
+            (CONTENT_TYPE, "text/css"),
+            (CONTENT_ENCODING, "identity"),
+        ])));
+    }
+
+    #[test]
+    fn honours_no_transform() {
+        assert!(!should_compress_response(&headers(&[
+            (CONTENT_TYPE, "text/css"),
+            (CACHE_CONTROL, "public, No-Transform"),
+        ])));
+        assert!(!should_compress_response(&headers(&[
+            (CONTENT_TYPE, "text/css"),
+            (CACHE_CONTROL, "max-age=60"),
+            (CACHE_CONTROL, "no-transform"),
+        ])));
+        assert!(should_compress_response(&headers(&[
+            (CONTENT_TYPE, "text/css"),
+            (CACHE_CONTROL, "no-cache"),
+        ])));
+    }
+}
@@ -0,0 +1,153 @@
+//! The full mime-db record for each media type, for callers that need more
+//! than the compressibility flag.
+//!
+//! ```
+//! use compressible::{lookup, Compressibility};
+//!
+//! let info = lookup("Application/JSON; charset=utf-8").unwrap();
+//!
+//! assert_eq!(info.essence, "application/json");
+//! assert_eq!(info.charset, Some("UTF-8"));
+//! assert_eq!(info.compressibility, Compressibility::Yes);
+//! assert_eq!(info.extensions, &["json", "map"]);
+//! ```
+use core::fmt;
+
+use crate::essence::Essence;
+use crate::{Compressibility, MEDIA_TYPES, MEDIA_TYPE_INFO};
+
+/// A media type and everything mime-db records about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MediaTypeInfo {
+    /// The lowercase `type/subtype`.
+    pub essence: &'static str,
+    /// Where mime-db took the entry from, if recorded.
+    pub source: Option<Source>,
+    /// The default charset for the type, as spelled in mime-db, e.g. `UTF-8`.
+    pub charset: Option<&'static str>,
+    /// Whether the type is compressible.
+    pub compressibility: Compressibility,
+    /// The lowercase file extensions for the type, most common first.
+    pub extensions: &'static [&'static str],
+}
+
+/// The origin of a mime-db entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// Apache's `mime.types`.
+    Apache,
+    /// The IANA media types registry.
+    Iana,
+    /// nginx's `mime.types`.
+    Nginx,
+}
+
+impl Source {
+    /// Returns the name mime-db uses for the source.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Source::Apache => "apache",
+            Source::Iana => "iana",
+            Source::Nginx => "nginx",
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Returns the mime-db record for the provided content type.
+///
+/// The content type is matched case-insensitively and any parameters are
+/// ignored. Returns `None` if it is malformed or not in the dataset.
+pub fn lookup(content_type: &str) -> Option<&'static MediaTypeInfo> {
+    Essence::parse(content_type).and_then(|essence| find(essence.as_str()))
+}
+
+/// Returns every media type in the dataset, sorted by essence.
+///
+/// ```
+/// use compressible::{media_types, Source};
+///
+/// let iana = media_types().filter(|info| info.source == Some(Source::Iana));
+/// assert!(iana.count() > 0);
+/// ```
+pub fn media_types() -> impl Iterator<Item = &'static MediaTypeInfo> {
+    MEDIA_TYPE_INFO.iter()
+}
+
+/// Looks up a lowercase `type/subtype` essence in the dataset.
+pub(crate) fn find(essence: &str) -> Option<&'static MediaTypeInfo> {
+    MEDIA_TYPES
+        .get(essence)
+        .map(|&index| &MEDIA_TYPE_INFO[index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_metadata() {
+        let info = lookup("application/javascript").unwrap();
+        assert_eq!(info.source, Some(Source::Iana));
+        assert_eq!(info.charset, Some("UTF-8"));
+        assert_eq!(info.extensions, &["js", "mjs"]);
+
+        let info = lookup("IMAGE/JPEG").unwrap();
+        assert_eq!(info.essence, "image/jpeg");
+        assert_eq!(info.compressibility, Compressibility::No);
+        assert!(info.extensions.contains(&"jpg"));
+
+        assert_eq!(lookup("application/x-unheard-of"), None);
+        assert_eq!(lookup("garbage"), None);
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(upstream_dataset),
+        ignore = "needs a verbatim upstream db.json, see scripts/update-dataset.sh"
+    )]
+    fn records_upstream_metadata() {
+        let info = lookup("application/3gpdash-qoe-report+xml").unwrap();
+        assert_eq!(info.source, Some(Source::Iana));
+        assert_eq!(info.charset, Some("UTF-8"));
+        assert_eq!(info.compressibility, Compressibility::Yes);
+
+        let info = lookup("application/vnd.ms-fontobject").unwrap();
+        assert_eq!(info.source, Some(Source::Iana));
+        assert_eq!(info.charset, None);
+        assert_eq!(info.compressibility, Compressibility::Yes);
+        assert_eq!(info.extensions, &["eot"]);
+    }
+
+    #[test]
+    fn entries_are_sorted_and_reachable() {
+        assert!(MEDIA_TYPE_INFO
+            .windows(2)
+            .all(|w| w[0].essence < w[1].essence));
+        for info in media_types() {
+            assert_eq!(lookup(info.essence), Some(info));
+        }
+    }
+
+    #[test]
+    fn matches_compressibility_tables() {
+        use crate::{CONTENT_TYPES, INCOMPRESSIBLE_TYPES};
+
+        let compressible =
+            media_types().filter(|info| info.compressibility == Compressibility::Yes);
+        assert!(compressible
+            .map(|info| info.essence)
+            .eq(CONTENT_TYPES.iter().copied()));
+
+        let incompressible =
+            media_types().filter(|info| info.compressibility == Compressibility::No);
+        assert!(incompressible
+            .map(|info| info.essence)
+            .eq(INCOMPRESSIBLE_TYPES.iter().copied()));
+    }
+}
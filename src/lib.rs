@@ -3,652 +3,341 @@
 //! Compressible checks if a provided media type is compressible using compression
 //! algorithms like brotli, gzip, deflate, etc.
 //!
-//! Dataset from https://github.com/jshttp/mime-db/blob/fa5e4ef3cc8907ec3c5ec5b85af0c63d7059a5cd/db.json
+//! The compressible flags come from mime-db at commit
+//! `fa5e4ef3cc8907ec3c5ec5b85af0c63d7059a5cd`. They are kept in mime-db's
+//! `db.json` format in `data/db.json` and turned into a lookup table by
+//! `build.rs`. That file is a reconstruction rather than a verbatim upstream
+//! copy: it lacks most of upstream's `source` and `charset` fields, which also
+//! affects which type some shared extensions resolve to, and will be replaced
+//! by the upstream file as is with `scripts/update-dataset.sh`. [`DATASET_VERSION`],
+//! [`DATASET_COMMIT`], [`DATASET_SHA256`] and [`DATASET_ENTRIES`] identify the
+//! embedded snapshot at runtime; [`DATASET_COMMIT`] is `None` until the file is
+//! a verbatim upstream copy.
 //!
 //! ```
 //! use compressible::is_compressible;
 //!
 //! assert_eq!(is_compressible("text/plain"), true);
 //! ```
+//!
+//! mime-db distinguishes types that are known not to be compressible from types
+//! it has no opinion on. Use [`compressibility`] to tell them apart:
+//!
+//! ```
+//! use compressible::{compressibility, Compressibility};
+//!
+//! assert_eq!(compressibility("text/html"), Compressibility::Yes);
+//! assert_eq!(compressibility("image/jpeg"), Compressibility::No);
+//! assert_eq!(compressibility("application/x-unheard-of"), Compressibility::Unknown);
+//! ```
+//!
+//! Types missing from the dataset can be classified by their structured syntax
+//! suffix with [`compressibility_with_heuristics`]:
+//!
+//! ```
+//! use compressible::{compressibility_with_heuristics, Compressibility};
+//!
+//! assert_eq!(
+//!     compressibility_with_heuristics("application/vnd.acme.report+json"),
+//!     Compressibility::Yes
+//! );
+//! ```
+//!
+//! The crate is `no_std` when the default `std` feature is disabled. The
+//! `Mime` based APIs are behind the default `mime` feature.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+use core::fmt;
+
+#[cfg(feature = "mime")]
 use mime::Mime;
 
+#[cfg(any(
+    feature = "gzip",
+    feature = "deflate",
+    feature = "brotli",
+    feature = "zstd",
+    feature = "async"
+))]
+pub mod encode;
+mod essence;
+#[cfg(feature = "std")]
+mod estimate;
+#[cfg(feature = "std")]
+pub mod export;
+mod extension;
+#[cfg(feature = "http")]
+mod headers;
+mod info;
+pub mod negotiate;
+#[cfg(feature = "std")]
+mod policy;
+#[cfg(any(
+    feature = "gzip",
+    feature = "deflate",
+    feature = "brotli",
+    feature = "zstd"
+))]
+pub mod precompress;
+#[cfg(feature = "tower")]
+mod predicate;
+mod query;
+#[cfg(feature = "std")]
+mod registry;
+#[cfg(feature = "std")]
+mod resolve;
+mod sniff;
+#[cfg(feature = "async")]
+pub mod stream;
+
+use essence::cmp_ignore_ascii_case;
+pub use essence::Essence;
+#[cfg(feature = "std")]
+pub use estimate::{estimate_compressibility, Estimate, EstimateOptions};
+#[cfg(feature = "std")]
+pub use extension::is_path_compressible;
+pub use extension::{extension_compressibility, extension_to_mime, is_extension_compressible};
+#[cfg(feature = "http")]
+pub use headers::should_compress_response;
+pub use info::{lookup, media_types, MediaTypeInfo, Source};
+pub use negotiate::Encoding;
+#[cfg(feature = "std")]
+pub use policy::{CompressionPolicy, Decision, ResponseMeta, SkipReason};
+#[cfg(feature = "tower")]
+pub use predicate::MimeDbPredicate;
+pub use query::{
+    compressible_types, compressible_types_with_prefix, compressible_types_with_suffix,
+    incompressible_types,
+};
+#[cfg(feature = "std")]
+pub use registry::{CompressibleRegistry, CompressibleRegistryBuilder, InvalidPattern};
+#[cfg(feature = "std")]
+pub use resolve::{ResolvedFile, SidecarResolver};
+pub use sniff::sniff_compressible;
+
+// Provenance constants, sorted lists of compressible and incompressible media
+// types, the full record for every media type with a perfect hash map over it,
+// and a sorted list of file extensions, generated by `build.rs`.
+include!(concat!(env!("OUT_DIR"), "/dataset.rs"));
+
+/// Compressibility of a media type as recorded in mime-db.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compressibility {
+    /// The dataset marks the type as compressible.
+    Yes,
+    /// The dataset explicitly marks the type as not compressible, e.g. `image/jpeg`.
+    No,
+    /// The dataset has no opinion on the type, or the content type could not
+    /// be parsed.
+    Unknown,
+}
+
+impl Compressibility {
+    /// Returns `yes`, `no` or `unknown`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Compressibility::Yes => "yes",
+            Compressibility::No => "no",
+            Compressibility::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for Compressibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Returns the [`Compressibility`] of the provided content type.
+///
+/// Unlike [`is_compressible`], this separates types that mime-db knows are not
+/// compressible from types it does not know about, so callers can apply their
+/// own heuristics to the latter only.
+///
+/// Only the `type/subtype` essence of the provided content_type is parsed,
+/// case-insensitively and without allocating. Returns
+/// `Compressibility::Unknown` if it is malformed.
+pub fn compressibility(content_type: &str) -> Compressibility {
+    match Essence::parse(content_type) {
+        Some(essence) => essence_compressibility(essence.as_str()),
+        None => Compressibility::Unknown,
+    }
+}
+
+/// Returns the [`Compressibility`] of an already parsed [`Mime`].
+#[cfg(feature = "mime")]
+pub fn mime_compressibility(mime: &Mime) -> Compressibility {
+    essence_compressibility(mime.essence_str())
+}
+
+/// Like [`compressibility`], but falls back to heuristics for types the
+/// dataset has no opinion on.
+///
+/// Explicit dataset entries always win. Otherwise the structured syntax suffix
+/// (`+json`, `+xml`, ...) is looked up with [`suffix_compressibility`], and
+/// any remaining `text/*` type is treated as compressible.
+///
+/// ```
+/// use compressible::{compressibility_with_heuristics, Compressibility};
+///
+/// assert_eq!(compressibility_with_heuristics("application/x-foo+xml"), Compressibility::Yes);
+/// assert_eq!(compressibility_with_heuristics("text/x-unlisted"), Compressibility::Yes);
+/// assert_eq!(compressibility_with_heuristics("application/x-foo+zip"), Compressibility::No);
+/// ```
+pub fn compressibility_with_heuristics(content_type: &str) -> Compressibility {
+    explain_compressibility(content_type).0
+}
+
+/// The rule [`explain_compressibility`] based its answer on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Basis {
+    /// The dataset entry for the type.
+    Dataset,
+    /// The structured syntax suffix, see [`suffix_compressibility`].
+    Suffix,
+    /// The `text/*` heuristic.
+    TextType,
+    /// No rule applies, or the content type could not be parsed.
+    Unmatched,
+}
+
+/// Like [`compressibility_with_heuristics`], but also returns the rule that
+/// decided the answer.
+///
+/// ```
+/// use compressible::{explain_compressibility, Basis, Compressibility};
+///
+/// assert_eq!(
+///     explain_compressibility("application/x-foo+json"),
+///     (Compressibility::Yes, Basis::Suffix)
+/// );
+/// assert_eq!(
+///     explain_compressibility("image/png"),
+///     (Compressibility::No, Basis::Dataset)
+/// );
+/// ```
+pub fn explain_compressibility(content_type: &str) -> (Compressibility, Basis) {
+    let essence = match Essence::parse(content_type) {
+        Some(essence) => essence,
+        None => return (Compressibility::Unknown, Basis::Unmatched),
+    };
+
+    match essence_compressibility(essence.as_str()) {
+        Compressibility::Unknown => {}
+        known => return (known, Basis::Dataset),
+    }
+
+    if let Some(suffix) = essence.suffix() {
+        match suffix_compressibility(suffix) {
+            Compressibility::Unknown => {}
+            known => return (known, Basis::Suffix),
+        }
+    }
+
+    if essence.type_() == "text" {
+        (Compressibility::Yes, Basis::TextType)
+    } else {
+        (Compressibility::Unknown, Basis::Unmatched)
+    }
+}
+
+/// Returns the [`Compressibility`] implied by a structured syntax suffix
+/// (RFC 6839), given without the leading `+`. The suffix is matched
+/// case-insensitively.
+///
+/// Text based syntaxes are compressible, suffixes naming an already compressed
+/// container are not, and anything else is `Compressibility::Unknown`.
+pub fn suffix_compressibility(suffix: &str) -> Compressibility {
+    // Important! Keep this list alphasorted.
+    const SUFFIX_POLICY: [(&str, Compressibility); 8] = [
+        ("cbor", Compressibility::Yes),
+        ("gzip", Compressibility::No),
+        ("json", Compressibility::Yes),
+        ("json-seq", Compressibility::Yes),
+        ("xml", Compressibility::Yes),
+        ("yaml", Compressibility::Yes),
+        ("zip", Compressibility::No),
+        ("zstd", Compressibility::No),
+    ];
+
+    const _: () = {
+        let mut i = 1;
+        while i < SUFFIX_POLICY.len() {
+            let ordering = cmp_ignore_ascii_case(SUFFIX_POLICY[i - 1].0, SUFFIX_POLICY[i].0);
+            assert!(matches!(ordering, core::cmp::Ordering::Less));
+            i += 1;
+        }
+    };
+
+    SUFFIX_POLICY
+        .binary_search_by(|(candidate, _)| cmp_ignore_ascii_case(candidate, suffix))
+        .map(|index| SUFFIX_POLICY[index].1)
+        .unwrap_or(Compressibility::Unknown)
+}
+
+/// Looks up a lowercase `type/subtype` essence in the dataset.
+fn essence_compressibility(essence: &str) -> Compressibility {
+    info::find(essence).map_or(Compressibility::Unknown, |info| info.compressibility)
+}
+
 /// Returns `bool` indicating whether the provided content type is compressible
 /// using compression algorithms like brotli, gzip, deflate, etc.
 ///
-/// The provided content_type is parsed using https://docs.rs/mime/0.3.16/mime/
-/// and returns `false` if the parsing fails.
+/// Returns `false` if the provided content_type is malformed, see
+/// [`compressibility`].
 pub fn is_compressible(content_type: &str) -> bool {
-    // Data obtained from https://github.com/jshttp/mime-db/blob/fa5e4ef3cc8907ec3c5ec5b85af0c63d7059a5cd/db.json
-    // Important! Keep this list alphasorted.
-    static CONTENT_TYPES: [&str; 612] = [
-        "application/3gpdash-qoe-report+xml",
-        "application/3gpp-ims+xml",
-        "application/3gpphal+json",
-        "application/3gpphalforms+json",
-        "application/activity+json",
-        "application/alto-costmap+json",
-        "application/alto-costmapfilter+json",
-        "application/alto-directory+json",
-        "application/alto-endpointcost+json",
-        "application/alto-endpointcostparams+json",
-        "application/alto-endpointprop+json",
-        "application/alto-endpointpropparams+json",
-        "application/alto-error+json",
-        "application/alto-networkmap+json",
-        "application/alto-networkmapfilter+json",
-        "application/alto-updatestreamcontrol+json",
-        "application/alto-updatestreamparams+json",
-        "application/atom+xml",
-        "application/atomcat+xml",
-        "application/atomdeleted+xml",
-        "application/atomsvc+xml",
-        "application/atsc-dwd+xml",
-        "application/atsc-held+xml",
-        "application/atsc-rdt+json",
-        "application/atsc-rsat+xml",
-        "application/auth-policy+xml",
-        "application/beep+xml",
-        "application/calendar+json",
-        "application/calendar+xml",
-        "application/captive+json",
-        "application/ccmp+xml",
-        "application/ccxml+xml",
-        "application/cdfx+xml",
-        "application/cea-2018+xml",
-        "application/cellml+xml",
-        "application/clue+xml",
-        "application/clue_info+xml",
-        "application/cnrp+xml",
-        "application/coap-group+json",
-        "application/conference-info+xml",
-        "application/cpl+xml",
-        "application/csta+xml",
-        "application/cstadata+xml",
-        "application/csvm+json",
-        "application/dart",
-        "application/dash+xml",
-        "application/davmount+xml",
-        "application/dialog-info+xml",
-        "application/dicom+json",
-        "application/dicom+xml",
-        "application/dns+json",
-        "application/docbook+xml",
-        "application/dskpp+xml",
-        "application/dssc+xml",
-        "application/ecmascript",
-        "application/elm+json",
-        "application/elm+xml",
-        "application/emergencycalldata.cap+xml",
-        "application/emergencycalldata.comment+xml",
-        "application/emergencycalldata.control+xml",
-        "application/emergencycalldata.deviceinfo+xml",
-        "application/emergencycalldata.providerinfo+xml",
-        "application/emergencycalldata.serviceinfo+xml",
-        "application/emergencycalldata.subscriberinfo+xml",
-        "application/emergencycalldata.veds+xml",
-        "application/emma+xml",
-        "application/emotionml+xml",
-        "application/epp+xml",
-        "application/expect-ct-report+json",
-        "application/fdt+xml",
-        "application/fhir+json",
-        "application/fhir+xml",
-        "application/fido.trusted-apps+json",
-        "application/framework-attributes+xml",
-        "application/geo+json",
-        "application/geoxacml+xml",
-        "application/gml+xml",
-        "application/gpx+xml",
-        "application/held+xml",
-        "application/ibe-key-request+xml",
-        "application/ibe-pkg-reply+xml",
-        "application/im-iscomposing+xml",
-        "application/inkml+xml",
-        "application/its+xml",
-        "application/javascript",
-        "application/jf2feed+json",
-        "application/jose+json",
-        "application/jrd+json",
-        "application/jscalendar+json",
-        "application/json",
-        "application/json-patch+json",
-        "application/jsonml+json",
-        "application/jwk+json",
-        "application/jwk-set+json",
-        "application/kpml-request+xml",
-        "application/kpml-response+xml",
-        "application/ld+json",
-        "application/lgr+xml",
-        "application/load-control+xml",
-        "application/lost+xml",
-        "application/lostsync+xml",
-        "application/mads+xml",
-        "application/manifest+json",
-        "application/marcxml+xml",
-        "application/mathml+xml",
-        "application/mathml-content+xml",
-        "application/mathml-presentation+xml",
-        "application/mbms-associated-procedure-description+xml",
-        "application/mbms-deregister+xml",
-        "application/mbms-envelope+xml",
-        "application/mbms-msk+xml",
-        "application/mbms-msk-response+xml",
-        "application/mbms-protection-description+xml",
-        "application/mbms-reception-report+xml",
-        "application/mbms-register+xml",
-        "application/mbms-register-response+xml",
-        "application/mbms-schedule+xml",
-        "application/mbms-user-service-description+xml",
-        "application/media-policy-dataset+xml",
-        "application/media_control+xml",
-        "application/mediaservercontrol+xml",
-        "application/merge-patch+json",
-        "application/metalink+xml",
-        "application/metalink4+xml",
-        "application/mets+xml",
-        "application/mmt-aei+xml",
-        "application/mmt-usd+xml",
-        "application/mods+xml",
-        "application/mrb-consumer+xml",
-        "application/mrb-publish+xml",
-        "application/msc-ivr+xml",
-        "application/msc-mixer+xml",
-        "application/mud+json",
-        "application/nlsml+xml",
-        "application/odm+xml",
-        "application/oebps-package+xml",
-        "application/omdoc+xml",
-        "application/opc-nodeset+xml",
-        "application/p2p-overlay+xml",
-        "application/patch-ops-error+xml",
-        "application/pidf+xml",
-        "application/pidf-diff+xml",
-        "application/pls+xml",
-        "application/poc-settings+xml",
-        "application/postscript",
-        "application/ppsp-tracker+json",
-        "application/problem+json",
-        "application/problem+xml",
-        "application/provenance+xml",
-        "application/prs.xsf+xml",
-        "application/pskc+xml",
-        "application/pvd+json",
-        "application/raml+yaml",
-        "application/rdap+json",
-        "application/rdf+xml",
-        "application/reginfo+xml",
-        "application/reputon+json",
-        "application/resource-lists+xml",
-        "application/resource-lists-diff+xml",
-        "application/rfc+xml",
-        "application/rlmi+xml",
-        "application/rls-services+xml",
-        "application/route-apd+xml",
-        "application/route-s-tsid+xml",
-        "application/route-usd+xml",
-        "application/rsd+xml",
-        "application/rss+xml",
-        "application/rtf",
-        "application/samlassertion+xml",
-        "application/samlmetadata+xml",
-        "application/sarif+json",
-        "application/sarif-external-properties+json",
-        "application/sbml+xml",
-        "application/scaip+xml",
-        "application/scim+json",
-        "application/senml+json",
-        "application/senml+xml",
-        "application/senml-etch+json",
-        "application/sensml+json",
-        "application/sensml+xml",
-        "application/sep+xml",
-        "application/shf+xml",
-        "application/simple-filter+xml",
-        "application/smil+xml",
-        "application/soap+xml",
-        "application/sparql-results+xml",
-        "application/spirits-event+xml",
-        "application/srgs+xml",
-        "application/sru+xml",
-        "application/ssdl+xml",
-        "application/ssml+xml",
-        "application/stix+json",
-        "application/swid+xml",
-        "application/tar",
-        "application/taxii+json",
-        "application/td+json",
-        "application/tei+xml",
-        "application/thraud+xml",
-        "application/tlsrpt+json",
-        "application/toml",
-        "application/ttml+xml",
-        "application/urc-grpsheet+xml",
-        "application/urc-ressheet+xml",
-        "application/urc-targetdesc+xml",
-        "application/urc-uisocketdesc+xml",
-        "application/vcard+json",
-        "application/vcard+xml",
-        "application/vnd.1000minds.decision-model+xml",
-        "application/vnd.3gpp-prose+xml",
-        "application/vnd.3gpp-prose-pc3ch+xml",
-        "application/vnd.3gpp.access-transfer-events+xml",
-        "application/vnd.3gpp.bsf+xml",
-        "application/vnd.3gpp.gmop+xml",
-        "application/vnd.3gpp.mcdata-affiliation-command+xml",
-        "application/vnd.3gpp.mcdata-info+xml",
-        "application/vnd.3gpp.mcdata-service-config+xml",
-        "application/vnd.3gpp.mcdata-ue-config+xml",
-        "application/vnd.3gpp.mcdata-user-profile+xml",
-        "application/vnd.3gpp.mcptt-affiliation-command+xml",
-        "application/vnd.3gpp.mcptt-floor-request+xml",
-        "application/vnd.3gpp.mcptt-info+xml",
-        "application/vnd.3gpp.mcptt-location-info+xml",
-        "application/vnd.3gpp.mcptt-mbms-usage-info+xml",
-        "application/vnd.3gpp.mcptt-service-config+xml",
-        "application/vnd.3gpp.mcptt-signed+xml",
-        "application/vnd.3gpp.mcptt-ue-config+xml",
-        "application/vnd.3gpp.mcptt-ue-init-config+xml",
-        "application/vnd.3gpp.mcptt-user-profile+xml",
-        "application/vnd.3gpp.mcvideo-affiliation-command+xml",
-        "application/vnd.3gpp.mcvideo-affiliation-info+xml",
-        "application/vnd.3gpp.mcvideo-info+xml",
-        "application/vnd.3gpp.mcvideo-location-info+xml",
-        "application/vnd.3gpp.mcvideo-mbms-usage-info+xml",
-        "application/vnd.3gpp.mcvideo-service-config+xml",
-        "application/vnd.3gpp.mcvideo-transmission-request+xml",
-        "application/vnd.3gpp.mcvideo-ue-config+xml",
-        "application/vnd.3gpp.mcvideo-user-profile+xml",
-        "application/vnd.3gpp.mid-call+xml",
-        "application/vnd.3gpp.sms+xml",
-        "application/vnd.3gpp.srvcc-ext+xml",
-        "application/vnd.3gpp.srvcc-info+xml",
-        "application/vnd.3gpp.state-and-event-info+xml",
-        "application/vnd.3gpp.ussd+xml",
-        "application/vnd.3gpp2.bcmcsinfo+xml",
-        "application/vnd.adobe.xdp+xml",
-        "application/vnd.amadeus+json",
-        "application/vnd.amundsen.maze+xml",
-        "application/vnd.api+json",
-        "application/vnd.aplextor.warrp+json",
-        "application/vnd.apothekende.reservation+json",
-        "application/vnd.apple.installer+xml",
-        "application/vnd.artisan+json",
-        "application/vnd.avalon+json",
-        "application/vnd.avistar+xml",
-        "application/vnd.balsamiq.bmml+xml",
-        "application/vnd.bbf.usp.msg+json",
-        "application/vnd.bekitzur-stech+json",
-        "application/vnd.biopax.rdf+xml",
-        "application/vnd.byu.uapi+json",
-        "application/vnd.capasystems-pg+json",
-        "application/vnd.chemdraw+xml",
-        "application/vnd.citationstyles.style+xml",
-        "application/vnd.collection+json",
-        "application/vnd.collection.doc+json",
-        "application/vnd.collection.next+json",
-        "application/vnd.coreos.ignition+json",
-        "application/vnd.criticaltools.wbs+xml",
-        "application/vnd.cryptii.pipe+json",
-        "application/vnd.ctct.ws+xml",
-        "application/vnd.cyan.dean.root+xml",
-        "application/vnd.cyclonedx+json",
-        "application/vnd.cyclonedx+xml",
-        "application/vnd.dart",
-        "application/vnd.datapackage+json",
-        "application/vnd.dataresource+json",
-        "application/vnd.dece.ttml+xml",
-        "application/vnd.dm.delegation+xml",
-        "application/vnd.document+json",
-        "application/vnd.drive+json",
-        "application/vnd.dvb.dvbisl+xml",
-        "application/vnd.dvb.notif-aggregate-root+xml",
-        "application/vnd.dvb.notif-container+xml",
-        "application/vnd.dvb.notif-generic+xml",
-        "application/vnd.dvb.notif-ia-msglist+xml",
-        "application/vnd.dvb.notif-ia-registration-request+xml",
-        "application/vnd.dvb.notif-ia-registration-response+xml",
-        "application/vnd.dvb.notif-init+xml",
-        "application/vnd.emclient.accessrequest+xml",
-        "application/vnd.eprints.data+xml",
-        "application/vnd.eszigno3+xml",
-        "application/vnd.etsi.aoc+xml",
-        "application/vnd.etsi.cug+xml",
-        "application/vnd.etsi.iptvcommand+xml",
-        "application/vnd.etsi.iptvdiscovery+xml",
-        "application/vnd.etsi.iptvprofile+xml",
-        "application/vnd.etsi.iptvsad-bc+xml",
-        "application/vnd.etsi.iptvsad-cod+xml",
-        "application/vnd.etsi.iptvsad-npvr+xml",
-        "application/vnd.etsi.iptvservice+xml",
-        "application/vnd.etsi.iptvsync+xml",
-        "application/vnd.etsi.iptvueprofile+xml",
-        "application/vnd.etsi.mcid+xml",
-        "application/vnd.etsi.overload-control-policy-dataset+xml",
-        "application/vnd.etsi.pstn+xml",
-        "application/vnd.etsi.sci+xml",
-        "application/vnd.etsi.simservs+xml",
-        "application/vnd.etsi.tsl+xml",
-        "application/vnd.fujifilm.fb.jfi+xml",
-        "application/vnd.futoin+json",
-        "application/vnd.gentics.grd+json",
-        "application/vnd.geo+json",
-        "application/vnd.geocube+xml",
-        "application/vnd.google-earth.kml+xml",
-        "application/vnd.gov.sk.e-form+xml",
-        "application/vnd.gov.sk.xmldatacontainer+xml",
-        "application/vnd.hal+json",
-        "application/vnd.hal+xml",
-        "application/vnd.handheld-entertainment+xml",
-        "application/vnd.hc+json",
-        "application/vnd.heroku+json",
-        "application/vnd.hyper+json",
-        "application/vnd.hyper-item+json",
-        "application/vnd.hyperdrive+json",
-        "application/vnd.ims.lis.v2.result+json",
-        "application/vnd.ims.lti.v2.toolconsumerprofile+json",
-        "application/vnd.ims.lti.v2.toolproxy+json",
-        "application/vnd.ims.lti.v2.toolproxy.id+json",
-        "application/vnd.ims.lti.v2.toolsettings+json",
-        "application/vnd.ims.lti.v2.toolsettings.simple+json",
-        "application/vnd.informedcontrol.rms+xml",
-        "application/vnd.infotech.project+xml",
-        "application/vnd.iptc.g2.catalogitem+xml",
-        "application/vnd.iptc.g2.conceptitem+xml",
-        "application/vnd.iptc.g2.knowledgeitem+xml",
-        "application/vnd.iptc.g2.newsitem+xml",
-        "application/vnd.iptc.g2.newsmessage+xml",
-        "application/vnd.iptc.g2.packageitem+xml",
-        "application/vnd.iptc.g2.planningitem+xml",
-        "application/vnd.irepository.package+xml",
-        "application/vnd.las.las+json",
-        "application/vnd.las.las+xml",
-        "application/vnd.leap+json",
-        "application/vnd.liberty-request+xml",
-        "application/vnd.llamagraphics.life-balance.exchange+xml",
-        "application/vnd.marlin.drm.actiontoken+xml",
-        "application/vnd.marlin.drm.conftoken+xml",
-        "application/vnd.marlin.drm.license+xml",
-        "application/vnd.mason+json",
-        "application/vnd.micro+json",
-        "application/vnd.miele+json",
-        "application/vnd.mozilla.xul+xml",
-        "application/vnd.ms-fontobject",
-        "application/vnd.ms-office.activex+xml",
-        "application/vnd.ms-opentype",
-        "application/vnd.ms-playready.initiator+xml",
-        "application/vnd.ms-printdevicecapabilities+xml",
-        "application/vnd.ms-printing.printticket+xml",
-        "application/vnd.ms-printschematicket+xml",
-        "application/vnd.nearst.inv+json",
-        "application/vnd.nokia.conml+xml",
-        "application/vnd.nokia.iptv.config+xml",
-        "application/vnd.nokia.landmark+xml",
-        "application/vnd.nokia.landmarkcollection+xml",
-        "application/vnd.nokia.n-gage.ac+xml",
-        "application/vnd.nokia.pcd+xml",
-        "application/vnd.oci.image.manifest.v1+json",
-        "application/vnd.oftn.l10n+json",
-        "application/vnd.oipf.contentaccessdownload+xml",
-        "application/vnd.oipf.contentaccessstreaming+xml",
-        "application/vnd.oipf.dae.svg+xml",
-        "application/vnd.oipf.dae.xhtml+xml",
-        "application/vnd.oipf.mippvcontrolmessage+xml",
-        "application/vnd.oipf.spdiscovery+xml",
-        "application/vnd.oipf.spdlist+xml",
-        "application/vnd.oipf.ueprofile+xml",
-        "application/vnd.oipf.userprofile+xml",
-        "application/vnd.oma.bcast.associated-procedure-parameter+xml",
-        "application/vnd.oma.bcast.drm-trigger+xml",
-        "application/vnd.oma.bcast.imd+xml",
-        "application/vnd.oma.bcast.notification+xml",
-        "application/vnd.oma.bcast.sgdd+xml",
-        "application/vnd.oma.bcast.smartcard-trigger+xml",
-        "application/vnd.oma.bcast.sprov+xml",
-        "application/vnd.oma.cab-address-book+xml",
-        "application/vnd.oma.cab-feature-handler+xml",
-        "application/vnd.oma.cab-pcc+xml",
-        "application/vnd.oma.cab-subs-invite+xml",
-        "application/vnd.oma.cab-user-prefs+xml",
-        "application/vnd.oma.dd2+xml",
-        "application/vnd.oma.drm.risd+xml",
-        "application/vnd.oma.group-usage-list+xml",
-        "application/vnd.oma.lwm2m+json",
-        "application/vnd.oma.pal+xml",
-        "application/vnd.oma.poc.detailed-progress-report+xml",
-        "application/vnd.oma.poc.final-report+xml",
-        "application/vnd.oma.poc.groups+xml",
-        "application/vnd.oma.poc.invocation-descriptor+xml",
-        "application/vnd.oma.poc.optimized-progress-report+xml",
-        "application/vnd.oma.scidm.messages+xml",
-        "application/vnd.oma.xcap-directory+xml",
-        "application/vnd.omads-email+xml",
-        "application/vnd.omads-file+xml",
-        "application/vnd.omads-folder+xml",
-        "application/vnd.openblox.game+xml",
-        "application/vnd.openstreetmap.data+xml",
-        "application/vnd.openxmlformats-officedocument.custom-properties+xml",
-        "application/vnd.openxmlformats-officedocument.customxmlproperties+xml",
-        "application/vnd.openxmlformats-officedocument.drawing+xml",
-        "application/vnd.openxmlformats-officedocument.drawingml.chart+xml",
-        "application/vnd.openxmlformats-officedocument.drawingml.chartshapes+xml",
-        "application/vnd.openxmlformats-officedocument.drawingml.diagramcolors+xml",
-        "application/vnd.openxmlformats-officedocument.drawingml.diagramdata+xml",
-        "application/vnd.openxmlformats-officedocument.drawingml.diagramlayout+xml",
-        "application/vnd.openxmlformats-officedocument.drawingml.diagramstyle+xml",
-        "application/vnd.openxmlformats-officedocument.extended-properties+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.commentauthors+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.comments+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.handoutmaster+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.notesmaster+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.notesslide+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.presentation.main+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.presprops+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.slide+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.slidelayout+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.slidemaster+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.slideshow.main+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.slideupdateinfo+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.tablestyles+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.tags+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.template.main+xml",
-        "application/vnd.openxmlformats-officedocument.presentationml.viewprops+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.calcchain+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.chartsheet+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.comments+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.connections+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.dialogsheet+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.externallink+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.pivotcachedefinition+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.pivotcacherecords+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.pivottable+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.querytable+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.revisionheaders+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.revisionlog+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.sharedstrings+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheetmetadata+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.table+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.tablesinglecells+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.template.main+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.usernames+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.volatiledependencies+xml",
-        "application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml",
-        "application/vnd.openxmlformats-officedocument.theme+xml",
-        "application/vnd.openxmlformats-officedocument.themeoverride+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.comments+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.document.glossary+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.endnotes+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.fonttable+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.footer+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.footnotes+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.settings+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.template.main+xml",
-        "application/vnd.openxmlformats-officedocument.wordprocessingml.websettings+xml",
-        "application/vnd.openxmlformats-package.core-properties+xml",
-        "application/vnd.openxmlformats-package.digital-signature-xmlsignature+xml",
-        "application/vnd.openxmlformats-package.relationships+xml",
-        "application/vnd.oracle.resource+json",
-        "application/vnd.otps.ct-kip+xml",
-        "application/vnd.pagerduty+json",
-        "application/vnd.poc.group-advertisement+xml",
-        "application/vnd.pwg-xhtml-print+xml",
-        "application/vnd.radisys.moml+xml",
-        "application/vnd.radisys.msml+xml",
-        "application/vnd.radisys.msml-audit+xml",
-        "application/vnd.radisys.msml-audit-conf+xml",
-        "application/vnd.radisys.msml-audit-conn+xml",
-        "application/vnd.radisys.msml-audit-dialog+xml",
-        "application/vnd.radisys.msml-audit-stream+xml",
-        "application/vnd.radisys.msml-conf+xml",
-        "application/vnd.radisys.msml-dialog+xml",
-        "application/vnd.radisys.msml-dialog-base+xml",
-        "application/vnd.radisys.msml-dialog-fax-detect+xml",
-        "application/vnd.radisys.msml-dialog-fax-sendrecv+xml",
-        "application/vnd.radisys.msml-dialog-group+xml",
-        "application/vnd.radisys.msml-dialog-speech+xml",
-        "application/vnd.radisys.msml-dialog-transform+xml",
-        "application/vnd.recordare.musicxml+xml",
-        "application/vnd.restful+json",
-        "application/vnd.route66.link66+xml",
-        "application/vnd.seis+json",
-        "application/vnd.shootproof+json",
-        "application/vnd.shopkick+json",
-        "application/vnd.siren+json",
-        "application/vnd.software602.filler.form+xml",
-        "application/vnd.solent.sdkm+xml",
-        "application/vnd.sun.wadl+xml",
-        "application/vnd.sycle+xml",
-        "application/vnd.syncml+xml",
-        "application/vnd.syncml.dm+xml",
-        "application/vnd.syncml.dmddf+xml",
-        "application/vnd.syncml.dmtnds+xml",
-        "application/vnd.tableschema+json",
-        "application/vnd.think-cell.ppttc+json",
-        "application/vnd.tmd.mediaflex.api+xml",
-        "application/vnd.uoml+xml",
-        "application/vnd.vel+json",
-        "application/vnd.wv.csp+xml",
-        "application/vnd.wv.ssp+xml",
-        "application/vnd.xacml+json",
-        "application/vnd.xmi+xml",
-        "application/vnd.yamaha.openscoreformat.osfpvg+xml",
-        "application/vnd.zzazz.deck+xml",
-        "application/voicexml+xml",
-        "application/voucher-cms+json",
-        "application/wasm",
-        "application/watcherinfo+xml",
-        "application/webpush-options+json",
-        "application/wsdl+xml",
-        "application/wspolicy+xml",
-        "application/x-dtbncx+xml",
-        "application/x-dtbook+xml",
-        "application/x-dtbresource+xml",
-        "application/x-httpd-php",
-        "application/x-javascript",
-        "application/x-ns-proxy-autoconfig",
-        "application/x-sh",
-        "application/x-tar",
-        "application/x-virtualbox-hdd",
-        "application/x-virtualbox-ova",
-        "application/x-virtualbox-ovf",
-        "application/x-virtualbox-vbox",
-        "application/x-virtualbox-vdi",
-        "application/x-virtualbox-vhd",
-        "application/x-virtualbox-vmdk",
-        "application/x-web-app-manifest+json",
-        "application/x-www-form-urlencoded",
-        "application/x-xliff+xml",
-        "application/xacml+xml",
-        "application/xaml+xml",
-        "application/xcap-att+xml",
-        "application/xcap-caps+xml",
-        "application/xcap-diff+xml",
-        "application/xcap-el+xml",
-        "application/xcap-error+xml",
-        "application/xcap-ns+xml",
-        "application/xcon-conference-info+xml",
-        "application/xcon-conference-info-diff+xml",
-        "application/xenc+xml",
-        "application/xhtml+xml",
-        "application/xhtml-voice+xml",
-        "application/xliff+xml",
-        "application/xml",
-        "application/xml-dtd",
-        "application/xml-patch+xml",
-        "application/xmpp+xml",
-        "application/xop+xml",
-        "application/xproc+xml",
-        "application/xslt+xml",
-        "application/xspf+xml",
-        "application/xv+xml",
-        "application/yang-data+json",
-        "application/yang-data+xml",
-        "application/yang-patch+json",
-        "application/yang-patch+xml",
-        "application/yin+xml",
-        "font/otf",
-        "font/ttf",
-        "image/bmp",
-        "image/svg+xml",
-        "image/vnd.adobe.photoshop",
-        "image/x-icon",
-        "image/x-ms-bmp",
-        "message/imdn+xml",
-        "message/rfc822",
-        "model/gltf+json",
-        "model/gltf-binary",
-        "model/vnd.collada+xml",
-        "model/vnd.moml+xml",
-        "model/x3d+xml",
-        "text/cache-manifest",
-        "text/calender",
-        "text/cmd",
-        "text/css",
-        "text/csv",
-        "text/html",
-        "text/javascript",
-        "text/jsx",
-        "text/less",
-        "text/markdown",
-        "text/mdx",
-        "text/n3",
-        "text/plain",
-        "text/richtext",
-        "text/rtf",
-        "text/tab-separated-values",
-        "text/uri-list",
-        "text/vcard",
-        "text/vtt",
-        "text/x-gwt-rpc",
-        "text/x-jquery-tmpl",
-        "text/x-markdown",
-        "text/x-org",
-        "text/x-processing",
-        "text/x-suse-ymp",
-        "text/xml",
-        "text/yaml",
-        "x-shader/x-fragment",
-        "x-shader/x-vertex",
-    ];
+    compressibility(content_type) == Compressibility::Yes
+}
 
-    if let Ok(content_type) = content_type.parse::<Mime>() {
-        CONTENT_TYPES
-            .binary_search(&content_type.essence_str())
-            .is_ok()
-    } else {
-        false
+/// Returns `bool` indicating whether an already parsed [`Mime`] is
+/// compressible, skipping the parsing done by [`is_compressible`].
+///
+/// ```
+/// use compressible::is_mime_compressible;
+///
+/// assert_eq!(is_mime_compressible(&mime::TEXT_HTML_UTF_8), true);
+/// assert_eq!(is_mime_compressible(&mime::IMAGE_JPEG), false);
+/// ```
+#[cfg(feature = "mime")]
+pub fn is_mime_compressible(mime: &Mime) -> bool {
+    mime_compressibility(mime) == Compressibility::Yes
+}
+
+/// Extension trait exposing the compressibility lookups as methods on [`Mime`].
+///
+/// ```
+/// use compressible::CompressibleExt;
+///
+/// assert!(mime::APPLICATION_JSON.is_compressible());
+/// ```
+#[cfg(feature = "mime")]
+pub trait CompressibleExt {
+    /// See [`mime_compressibility`].
+    fn compressibility(&self) -> Compressibility;
+
+    /// See [`is_mime_compressible`].
+    fn is_compressible(&self) -> bool {
+        self.compressibility() == Compressibility::Yes
+    }
+}
+
+#[cfg(feature = "mime")]
+impl CompressibleExt for Mime {
+    fn compressibility(&self) -> Compressibility {
+        mime_compressibility(self)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::is_compressible;
+    use crate::{
+        compressibility, compressibility_with_heuristics, is_compressible, suffix_compressibility,
+        Compressibility,
+    };
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn it_works() {
         assert_eq!(is_compressible("text/plain"), true);
         assert_eq!(is_compressible("application/x-web-app-manifest+json"), true);
@@ -656,4 +345,195 @@ mod tests {
         assert_eq!(is_compressible("image/jpeg; param=1"), false);
         assert_eq!(is_compressible("as;ldfkjas;ldfkja;lsdfj"), false);
     }
+
+    #[test]
+    fn compressibility_is_tri_state() {
+        assert_eq!(
+            compressibility("text/html; charset=utf-8"),
+            Compressibility::Yes
+        );
+        assert_eq!(compressibility("image/jpeg"), Compressibility::No);
+        assert_eq!(compressibility("application/zip"), Compressibility::No);
+        assert_eq!(compressibility("image/webp"), Compressibility::Unknown);
+        assert_eq!(
+            compressibility("not a media type"),
+            Compressibility::Unknown
+        );
+    }
+
+    #[cfg(feature = "mime")]
+    #[test]
+    fn mime_api_matches_str_api() {
+        use crate::{is_mime_compressible, CompressibleExt};
+
+        for content_type in &[
+            "text/plain",
+            "image/png",
+            "application/x-unknown",
+            "TEXT/CSS",
+        ] {
+            let mime: mime::Mime = content_type.parse().unwrap();
+            assert_eq!(mime.compressibility(), compressibility(content_type));
+            assert_eq!(is_mime_compressible(&mime), is_compressible(content_type));
+        }
+    }
+
+    #[test]
+    fn heuristics_only_apply_to_unknown_types() {
+        use Compressibility::*;
+
+        assert_eq!(compressibility("application/vnd.acme.report+json"), Unknown);
+        assert_eq!(
+            compressibility_with_heuristics("application/vnd.acme.report+json"),
+            Yes
+        );
+        assert_eq!(
+            compressibility_with_heuristics("application/x-foo+yaml; v=1"),
+            Yes
+        );
+        assert_eq!(compressibility_with_heuristics("text/x-whatever"), Yes);
+        assert_eq!(
+            compressibility_with_heuristics("application/x-foo+der"),
+            Unknown
+        );
+        assert_eq!(compressibility_with_heuristics("image/jpeg"), No);
+        assert_eq!(compressibility_with_heuristics("application/epub+zip"), No);
+        assert_eq!(compressibility_with_heuristics("nonsense"), Unknown);
+    }
+
+    #[test]
+    fn explains_the_deciding_rule() {
+        use crate::{explain_compressibility, Basis};
+        use Compressibility::*;
+
+        assert_eq!(explain_compressibility("TEXT/CSS"), (Yes, Basis::Dataset));
+        assert_eq!(explain_compressibility("image/jpeg"), (No, Basis::Dataset));
+        assert_eq!(
+            explain_compressibility("application/x-foo+zip"),
+            (No, Basis::Suffix)
+        );
+        assert_eq!(
+            explain_compressibility("text/x-whatever"),
+            (Yes, Basis::TextType)
+        );
+        assert_eq!(
+            explain_compressibility("image/x-foo"),
+            (Unknown, Basis::Unmatched)
+        );
+        assert_eq!(
+            explain_compressibility("nonsense"),
+            (Unknown, Basis::Unmatched)
+        );
+    }
+
+    #[test]
+    fn suffix_policy() {
+        assert_eq!(suffix_compressibility("json"), Compressibility::Yes);
+        assert_eq!(suffix_compressibility("zip"), Compressibility::No);
+        assert_eq!(suffix_compressibility("ber"), Compressibility::Unknown);
+        assert_eq!(suffix_compressibility("JSON"), Compressibility::Yes);
+        assert_eq!(suffix_compressibility("Json-Seq"), Compressibility::Yes);
+        assert_eq!(suffix_compressibility("ZIP"), Compressibility::No);
+    }
+
+    /// Upper, alternating and parameterised spellings of every dataset entry
+    /// must resolve like the entry itself.
+    #[test]
+    fn mixed_case_matches_every_entry() {
+        use crate::{extension_to_mime, CONTENT_TYPES, EXTENSIONS, INCOMPRESSIBLE_TYPES};
+
+        fn variants(input: &str) -> [String; 3] {
+            let alternating = input
+                .chars()
+                .enumerate()
+                .map(|(i, c)| {
+                    if i % 2 == 0 {
+                        c.to_ascii_uppercase()
+                    } else {
+                        c
+                    }
+                })
+                .collect();
+            [
+                input.to_ascii_uppercase(),
+                alternating,
+                format!("{}; Charset=UTF-8", input.to_ascii_uppercase()),
+            ]
+        }
+
+        let tables = [
+            (&CONTENT_TYPES[..], Compressibility::Yes),
+            (&INCOMPRESSIBLE_TYPES[..], Compressibility::No),
+        ];
+        for (table, expected) in tables.iter() {
+            for media_type in table.iter() {
+                for variant in variants(media_type).iter() {
+                    assert_eq!(compressibility(variant), *expected, "{}", variant);
+                    assert_eq!(
+                        compressibility_with_heuristics(variant),
+                        *expected,
+                        "{}",
+                        variant
+                    );
+                }
+            }
+        }
+
+        for (extension, media_type) in EXTENSIONS.iter() {
+            for variant in variants(extension).iter().take(2) {
+                assert_eq!(extension_to_mime(variant), Some(*media_type), "{}", variant);
+            }
+        }
+
+        for variant in variants("application/vnd.acme.report+json").iter() {
+            assert_eq!(
+                compressibility_with_heuristics(variant),
+                Compressibility::Yes,
+                "{}",
+                variant
+            );
+        }
+    }
+
+    #[test]
+    fn dataset_provenance() {
+        use crate::{
+            media_types, DATASET_COMMIT, DATASET_ENTRIES, DATASET_SHA256, DATASET_VERSION,
+        };
+
+        let is_hex = |value: &str| value.bytes().all(|b| b.is_ascii_hexdigit());
+        assert_eq!(DATASET_ENTRIES, media_types().count());
+        assert!(DATASET_COMMIT.is_none_or(|commit| commit.len() == 40 && is_hex(commit)));
+        assert!(DATASET_COMMIT.is_some() || DATASET_VERSION == "unreleased");
+        assert_eq!(DATASET_SHA256.len(), 64);
+        assert!(is_hex(DATASET_SHA256));
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(upstream_dataset),
+        ignore = "needs a verbatim upstream db.json, see scripts/update-dataset.sh"
+    )]
+    fn dataset_is_pinned_upstream() {
+        use crate::{DATASET_COMMIT, DATASET_VERSION};
+
+        assert_eq!(
+            DATASET_COMMIT,
+            Some("fa5e4ef3cc8907ec3c5ec5b85af0c63d7059a5cd")
+        );
+        assert_ne!(DATASET_VERSION, "unreleased");
+    }
+
+    #[test]
+    fn displays_compressibility() {
+        assert_eq!(Compressibility::Yes.to_string(), "yes");
+        assert_eq!(Compressibility::No.to_string(), "no");
+        assert_eq!(Compressibility::Unknown.as_str(), "unknown");
+    }
+
+    #[test]
+    fn content_types_are_sorted() {
+        assert!(crate::CONTENT_TYPES.windows(2).all(|w| w[0] < w[1]));
+        assert!(crate::INCOMPRESSIBLE_TYPES.windows(2).all(|w| w[0] < w[1]));
+    }
 }
@@ -0,0 +1,496 @@
+//! Command-line interface for auditing content types and file paths against
+//! the dataset, for precompressing static sites and for exporting web server
+//! configuration.
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand};
+use compressible::export::{render, Format};
+use compressible::precompress::{Manifest, Precompressor};
+use compressible::{
+    compressible_types_with_prefix, compressible_types_with_suffix, explain_compressibility,
+    extension_to_mime, lookup, Basis, Compressibility, Encoding, Essence,
+};
+use serde::Serialize;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Report whether content types or file paths are compressible.
+    Check(CheckArgs),
+    /// Write `.gz`, `.br` and `.zst` sidecars for compressible files in a
+    /// directory.
+    Precompress(PrecompressArgs),
+    /// Print the compressible types as web server configuration.
+    Export(ExportArgs),
+}
+
+#[derive(Args)]
+struct CheckArgs {
+    /// Content types or file paths. Read from stdin, one per line, when
+    /// omitted.
+    inputs: Vec<String>,
+
+    /// Treat every input as a file path, even if it parses as a content type.
+    #[arg(long)]
+    paths: bool,
+
+    /// Fall back to structured syntax suffix and `text/*` heuristics for types
+    /// missing from mime-db.
+    #[arg(long)]
+    heuristics: bool,
+
+    /// Print the results as a JSON array.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct PrecompressArgs {
+    /// Directory to walk.
+    root: PathBuf,
+
+    /// Encodings to write sidecars for.
+    #[arg(
+        long = "encoding",
+        value_delimiter = ',',
+        default_values = ["gzip", "br", "zstd"],
+        value_parser = sidecar_encoding()
+    )]
+    encodings: Vec<Encoding>,
+
+    /// Write the JSON manifest to this file instead of stdout.
+    #[arg(long)]
+    manifest: Option<PathBuf>,
+}
+
+/// Accepts only the encodings that have a sidecar file extension.
+fn sidecar_encoding() -> impl TypedValueParser<Value = Encoding> {
+    let names: Vec<&str> = Encoding::ALL
+        .iter()
+        .filter(|encoding| encoding.file_extension().is_some())
+        .map(|encoding| encoding.as_str())
+        .collect();
+    PossibleValuesParser::new(names).map(|name| name.parse().expect("encoding names parse"))
+}
+
+#[derive(Args)]
+struct ExportArgs {
+    /// One of nginx-gzip, nginx-brotli, apache, caddy, haproxy or envoy.
+    format: Format,
+
+    /// Only export types starting with this prefix, e.g. `text/`.
+    #[arg(long)]
+    prefix: Option<String>,
+
+    /// Only export types with this structured syntax suffix, e.g. `json`.
+    #[arg(long)]
+    suffix: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    input: String,
+    kind: &'static str,
+    media_type: Option<String>,
+    result: &'static str,
+    rule: String,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Check(args) => check(args),
+        Command::Precompress(args) => precompress(args),
+        Command::Export(args) => export(&args).map(|config| print!("{}", config)),
+    };
+    if let Err(err) = result {
+        eprintln!("compressible: {}", err);
+        process::exit(1);
+    }
+}
+
+fn check(args: CheckArgs) -> io::Result<()> {
+    let inputs = if args.inputs.is_empty() {
+        io::stdin()
+            .lock()
+            .lines()
+            .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+            .map(|line| line.map(|line| line.trim().to_owned()))
+            .collect::<io::Result<Vec<_>>>()?
+    } else {
+        args.inputs
+    };
+
+    let (paths, heuristics) = (args.paths, args.heuristics);
+    let reports: Vec<Report> = inputs
+        .into_iter()
+        .map(|input| report(input, paths, heuristics))
+        .collect();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+    } else {
+        for report in reports {
+            println!("{}\t{}\t{}", report.input, report.result, report.rule);
+        }
+    }
+    Ok(())
+}
+
+fn report(input: String, paths: bool, heuristics: bool) -> Report {
+    let content_type = if paths { None } else { as_content_type(&input) };
+
+    if let Some(essence) = content_type {
+        let (compressibility, rule) = classify(&essence, heuristics);
+        return Report {
+            kind: "content-type",
+            media_type: Some(essence.as_str().to_owned()),
+            result: compressibility.as_str(),
+            rule,
+            input,
+        };
+    }
+
+    let extension = Path::new(&input)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_owned);
+    let (media_type, compressibility, rule) = match extension {
+        None => (
+            None,
+            Compressibility::Unknown,
+            "no file extension".to_owned(),
+        ),
+        Some(extension) => match extension_to_mime(&extension) {
+            None => (
+                None,
+                Compressibility::Unknown,
+                format!("unknown extension .{}", extension),
+            ),
+            Some(media_type) => {
+                let essence = Essence::parse(media_type).expect("dataset types are valid");
+                let (compressibility, rule) = classify(&essence, heuristics);
+                (
+                    Some(media_type.to_owned()),
+                    compressibility,
+                    format!("extension .{} is {}, {}", extension, media_type, rule),
+                )
+            }
+        },
+    };
+
+    Report {
+        kind: "path",
+        media_type,
+        result: compressibility.as_str(),
+        rule,
+        input,
+    }
+}
+
+/// Top-level types registered with IANA.
+const TOP_LEVEL_TYPES: [&str; 11] = [
+    "application",
+    "audio",
+    "example",
+    "font",
+    "haptics",
+    "image",
+    "message",
+    "model",
+    "multipart",
+    "text",
+    "video",
+];
+
+/// Parses `input` as a content type if it looks like one rather than like a
+/// path such as `assets/app.js`: its top-level type must be registered, or the
+/// type must be in the dataset. Whether a file exists is deliberately ignored,
+/// so audits give the same answer on any machine.
+fn as_content_type(input: &str) -> Option<Essence> {
+    let essence = Essence::parse(input)?;
+    if TOP_LEVEL_TYPES.contains(&essence.type_()) || lookup(essence.as_str()).is_some() {
+        Some(essence)
+    } else {
+        None
+    }
+}
+
+fn precompress(args: PrecompressArgs) -> io::Result<()> {
+    let manifest = Precompressor::new()
+        .encodings(&args.encodings)
+        .run(&args.root)?;
+    let json = serde_json::to_string_pretty(&manifest_json(&manifest))?;
+
+    match args.manifest {
+        Some(path) => fs::write(path, json + "\n"),
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
+
+/// Renders the exported configuration, failing if the filters leave nothing to
+/// export.
+fn export(args: &ExportArgs) -> io::Result<String> {
+    let prefix = args.prefix.as_deref().unwrap_or("");
+    let types: Vec<&str> = match &args.suffix {
+        Some(suffix) => {
+            let with_suffix: Vec<&str> = compressible_types_with_suffix(suffix).collect();
+            compressible_types_with_prefix(prefix)
+                .filter(|media_type| with_suffix.contains(media_type))
+                .collect()
+        }
+        None => compressible_types_with_prefix(prefix).collect(),
+    };
+
+    let config = render(args.format, types);
+    if config.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("no compressible types to export as {}", args.format),
+        ));
+    }
+    Ok(config)
+}
+
+#[derive(Serialize)]
+struct ManifestJson<'a> {
+    files: Vec<FileJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct FileJson<'a> {
+    path: &'a Path,
+    content_type: &'static str,
+    size: u64,
+    sidecars: Vec<SidecarJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct SidecarJson<'a> {
+    encoding: &'static str,
+    path: &'a Path,
+    size: u64,
+}
+
+fn manifest_json(manifest: &Manifest) -> ManifestJson<'_> {
+    let files = manifest
+        .files
+        .iter()
+        .map(|file| FileJson {
+            path: &file.path,
+            content_type: file.content_type,
+            size: file.size,
+            sidecars: file
+                .sidecars
+                .iter()
+                .map(|sidecar| SidecarJson {
+                    encoding: sidecar.encoding.as_str(),
+                    path: &sidecar.path,
+                    size: sidecar.size,
+                })
+                .collect(),
+        })
+        .collect();
+    ManifestJson { files }
+}
+
+/// Returns the compressibility of `essence` along with a description of the rule
+/// that decided it. Without `heuristics`, only dataset entries count.
+fn classify(essence: &Essence, heuristics: bool) -> (Compressibility, String) {
+    match explain_compressibility(essence.as_str()) {
+        (compressibility, Basis::Dataset) => {
+            (compressibility, format!("mime-db entry {}", essence))
+        }
+        (compressibility, Basis::Suffix) if heuristics => (
+            compressibility,
+            format!("suffix +{}", essence.suffix().unwrap_or_default()),
+        ),
+        (compressibility, Basis::TextType) if heuristics => {
+            (compressibility, "text/* heuristic".to_owned())
+        }
+        _ => (
+            Compressibility::Unknown,
+            "no mime-db compressible flag".to_owned(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_one(input: &str, paths: bool, heuristics: bool) -> Report {
+        report(input.to_owned(), paths, heuristics)
+    }
+
+    #[test]
+    fn tells_content_types_from_paths() {
+        let checked = check_one("Text/CSS; charset=utf-8", false, false);
+        assert_eq!((checked.kind, checked.result), ("content-type", "yes"));
+        assert_eq!(checked.rule, "mime-db entry text/css");
+
+        // Parsed like the library does, ignoring malformed parameters.
+        let checked = check_one("text/html; charset", false, false);
+        assert_eq!((checked.kind, checked.result), ("content-type", "yes"));
+        assert_eq!(
+            checked.result,
+            compressible::compressibility("text/html; charset").as_str()
+        );
+
+        // Neither on disk nor a registered top-level type.
+        let checked = check_one("assets/app.js", false, false);
+        assert_eq!((checked.kind, checked.result), ("path", "yes"));
+        assert_eq!(
+            checked.rule,
+            "extension .js is application/javascript, mime-db entry application/javascript"
+        );
+
+        // Parses as a content type, so only `--paths` makes it a path.
+        let checked = check_one("image/logo.png", false, false);
+        assert_eq!((checked.kind, checked.result), ("content-type", "unknown"));
+        let checked = check_one("image/logo.png", true, false);
+        assert_eq!((checked.kind, checked.result), ("path", "no"));
+        assert_eq!(checked.media_type.as_deref(), Some("image/png"));
+    }
+
+    #[test]
+    fn reports_the_deciding_rule() {
+        let checked = check_one("application/vnd.acme+json", false, false);
+        assert_eq!(checked.result, "unknown");
+        assert_eq!(checked.rule, "no mime-db compressible flag");
+
+        let checked = check_one("application/vnd.acme+json", false, true);
+        assert_eq!(
+            (checked.result, checked.rule.as_str()),
+            ("yes", "suffix +json")
+        );
+
+        let checked = check_one("text/x-unlisted", false, true);
+        assert_eq!(
+            (checked.result, checked.rule.as_str()),
+            ("yes", "text/* heuristic")
+        );
+
+        let checked = check_one("docs/README", false, false);
+        assert_eq!(
+            (checked.result, checked.rule.as_str()),
+            ("unknown", "no file extension")
+        );
+
+        let checked = check_one("docs/notes.unheardof", false, false);
+        assert_eq!(checked.rule, "unknown extension .unheardof");
+    }
+
+    #[test]
+    fn report_json_shape() {
+        let reports = vec![
+            check_one("text/html", false, false),
+            check_one("notes.unheardof", false, false),
+        ];
+        assert_eq!(
+            serde_json::to_value(&reports).unwrap(),
+            serde_json::json!([
+                {
+                    "input": "text/html",
+                    "kind": "content-type",
+                    "media_type": "text/html",
+                    "result": "yes",
+                    "rule": "mime-db entry text/html"
+                },
+                {
+                    "input": "notes.unheardof",
+                    "kind": "path",
+                    "media_type": null,
+                    "result": "unknown",
+                    "rule": "unknown extension .unheardof"
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn only_sidecar_encodings_are_accepted() {
+        let parse = |encodings: &str| {
+            Cli::try_parse_from(["compressible", "precompress", "--encoding", encodings, "."]).map(
+                |cli| match cli.command {
+                    Command::Precompress(args) => args.encodings,
+                    _ => unreachable!(),
+                },
+            )
+        };
+        assert_eq!(
+            parse("gzip,br,zstd").unwrap(),
+            [Encoding::Gzip, Encoding::Brotli, Encoding::Zstd]
+        );
+        assert!(parse("deflate").is_err());
+        assert!(parse("gzip,identity").is_err());
+    }
+
+    #[test]
+    fn manifest_json_shape() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let css = "body { margin: 0; padding: 0; }\n".repeat(100);
+        fs::write(root.join("site.css"), &css).unwrap();
+
+        let manifest = Precompressor::new()
+            .encodings(&[Encoding::Gzip])
+            .run(root)
+            .unwrap();
+        let gzipped = fs::metadata(root.join("site.css.gz")).unwrap().len();
+        assert_eq!(
+            serde_json::to_value(manifest_json(&manifest)).unwrap(),
+            serde_json::json!({
+                "files": [{
+                    "path": "site.css",
+                    "content_type": "text/css",
+                    "size": css.len(),
+                    "sidecars": [{
+                        "encoding": "gzip",
+                        "path": "site.css.gz",
+                        "size": gzipped
+                    }]
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn exports_filtered_types() {
+        let export_args = |args: &[&str]| {
+            let cli = Cli::try_parse_from(["compressible", "export"].iter().chain(args)).unwrap();
+            match cli.command {
+                Command::Export(args) => export(&args).map_err(|err| err.to_string()),
+                _ => unreachable!(),
+            }
+        };
+
+        assert_eq!(
+            export_args(&["apache", "--prefix", "image/", "--suffix", "xml"]),
+            Ok("AddOutputFilterByType DEFLATE image/svg+xml\n".to_owned())
+        );
+
+        let config = export_args(&["haproxy", "--suffix", "json"]).unwrap();
+        assert!(config.starts_with("compression type application/"));
+
+        assert_eq!(
+            export_args(&["nginx-gzip", "--prefix", "text/html"]),
+            Err("no compressible types to export as nginx-gzip".to_owned())
+        );
+        assert!(export_args(&["envoy", "--prefix", "zzz/"]).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//! `Accept-Encoding` negotiation.
+//!
+//! ```
+//! use compressible::negotiate::{select_encoding, Encoding};
+//!
+//! let accept_encoding = "gzip, br;q=0.9, *;q=0.1";
+//!
+//! assert_eq!(
+//!     select_encoding("text/html", accept_encoding, &Encoding::ALL),
+//!     Some(Encoding::Gzip)
+//! );
+//! assert_eq!(
+//!     select_encoding("image/png", accept_encoding, &Encoding::ALL),
+//!     Some(Encoding::Identity)
+//! );
+//! ```
+use core::fmt;
+use core::iter;
+use core::str::FromStr;
+
+use crate::{compressibility_with_heuristics, is_compressible, Compressibility};
+
+/// A content coding as used in `Accept-Encoding` and `Content-Encoding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// `br`
+    Brotli,
+    /// `zstd`
+    Zstd,
+    /// `gzip`
+    Gzip,
+    /// `deflate`
+    Deflate,
+    /// `identity`, i.e. no compression.
+    Identity,
+}
+
+impl Encoding {
+    /// Every encoding, in the order servers usually prefer them.
+    pub const ALL: [Encoding; 5] = [
+        Encoding::Brotli,
+        Encoding::Zstd,
+        Encoding::Gzip,
+        Encoding::Deflate,
+        Encoding::Identity,
+    ];
+
+    /// Returns the token used for this encoding in HTTP headers.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Zstd => "zstd",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Identity => "identity",
+        }
+    }
+
+    /// Returns the file extension conventionally used for precompressed
+    /// copies of a file, such as `gz` for `app.js.gz`. `Encoding::Deflate` and
+    /// `Encoding::Identity` have none.
+    pub fn file_extension(self) -> Option<&'static str> {
+        match self {
+            Encoding::Brotli => Some("br"),
+            Encoding::Zstd => Some("zst"),
+            Encoding::Gzip => Some("gz"),
+            Encoding::Deflate | Encoding::Identity => None,
+        }
+    }
+
+    fn matches(self, token: &str) -> bool {
+        token.eq_ignore_ascii_case(self.as_str())
+            || (self == Encoding::Gzip && token.eq_ignore_ascii_case("x-gzip"))
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = ParseEncodingError;
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        Encoding::ALL
+            .iter()
+            .copied()
+            .find(|encoding| encoding.matches(token.trim()))
+            .ok_or(ParseEncodingError)
+    }
+}
+
+/// Error returned when parsing an unsupported content coding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEncodingError;
+
+impl fmt::Display for ParseEncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unsupported content coding")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseEncodingError {}
+
+/// Returns the encoding to use for a response with the provided content type.
+///
+/// Content types that are not compressible according to [`is_compressible`]
+/// are always sent as `Encoding::Identity`. Otherwise this is
+/// [`preferred_encoding`].
+pub fn select_encoding(
+    content_type: &str,
+    accept_encoding: &str,
+    supported: &[Encoding],
+) -> Option<Encoding> {
+    select(is_compressible(content_type), accept_encoding, supported)
+}
+
+/// Like [`select_encoding`], but decides compressibility with
+/// [`compressibility_with_heuristics`], for content types such as
+/// `text/event-stream` that are often missing from mime-db.
+///
+/// ```
+/// use compressible::negotiate::{select_encoding_with_heuristics, Encoding};
+///
+/// assert_eq!(
+///     select_encoding_with_heuristics("application/vnd.acme+json", "gzip", &Encoding::ALL),
+///     Some(Encoding::Gzip)
+/// );
+/// ```
+pub fn select_encoding_with_heuristics(
+    content_type: &str,
+    accept_encoding: &str,
+    supported: &[Encoding],
+) -> Option<Encoding> {
+    let compressible = compressibility_with_heuristics(content_type) == Compressibility::Yes;
+    select(compressible, accept_encoding, supported)
+}
+
+fn select(compressible: bool, accept_encoding: &str, supported: &[Encoding]) -> Option<Encoding> {
+    if compressible {
+        preferred_encoding(accept_encoding, supported)
+    } else {
+        Some(Encoding::Identity)
+    }
+}
+
+/// Returns the encoding in `supported` the client prefers according to its
+/// `Accept-Encoding` header value, honouring q-values and `*`.
+///
+/// Ties are broken by the order of `supported`. `Encoding::Identity` is always
+/// considered acceptable to the server and is picked when nothing else is;
+/// `None` means the client refused every encoding including `identity`, and
+/// the response should be `406 Not Acceptable`.
+///
+/// An empty header value means only `identity` is acceptable. Callers
+/// handling a request without the header may send any encoding, but usually
+/// pass an empty string.
+pub fn preferred_encoding(accept_encoding: &str, supported: &[Encoding]) -> Option<Encoding> {
+    let identity = if supported.contains(&Encoding::Identity) {
+        None
+    } else {
+        Some(Encoding::Identity)
+    };
+
+    let mut best: Option<(Encoding, u16)> = None;
+    for &encoding in supported.iter().chain(identity.iter()) {
+        let q = quality(accept_encoding, encoding);
+        if q > 0 && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((encoding, q));
+        }
+    }
+    best.map(|(encoding, _)| encoding)
+}
+
+/// Returns the q-value of `encoding` in thousandths.
+fn quality(accept_encoding: &str, encoding: Encoding) -> u16 {
+    let mut explicit = None;
+    let mut wildcard = None;
+
+    for entry in accept_encoding.split(',') {
+        let mut parts = entry.split(';');
+        let token = parts.next().unwrap_or_default().trim();
+        if token.is_empty() {
+            continue;
+        }
+
+        let mut q = Some(1000);
+        for param in parts {
+            if let Some((name, value)) = param.split_once('=') {
+                if name.trim().eq_ignore_ascii_case("q") {
+                    q = parse_q(value.trim());
+                }
+            }
+        }
+        let q = match q {
+            Some(q) => q,
+            None => continue,
+        };
+
+        if token == "*" {
+            wildcard = Some(q);
+        } else if encoding.matches(token) {
+            explicit = Some(q);
+        }
+    }
+
+    explicit.or(wildcard).unwrap_or(match encoding {
+        Encoding::Identity => 1,
+        _ => 0,
+    })
+}
+
+/// Parses a `qvalue` (RFC 9110 §12.4.2) into thousandths.
+fn parse_q(value: &str) -> Option<u16> {
+    let (int, frac) = value.split_once('.').unwrap_or((value, ""));
+    if frac.len() > 3 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let frac = frac
+        .bytes()
+        .chain(iter::repeat(b'0'))
+        .take(3)
+        .fold(0, |acc, b| acc * 10 + u16::from(b - b'0'));
+    match int {
+        "0" => Some(frac),
+        "1" if frac == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn honours_q_values() {
+        assert_eq!(
+            preferred_encoding("gzip;q=0.5, br;q=0.8", &Encoding::ALL),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(
+            preferred_encoding("gzip, deflate, br", &Encoding::ALL),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(
+            preferred_encoding("gzip, deflate, br", &[Encoding::Gzip, Encoding::Brotli]),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(
+            preferred_encoding("X-GZIP", &Encoding::ALL),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(
+            preferred_encoding("br;q=0, gzip;q=1.5, zstd", &Encoding::ALL),
+            Some(Encoding::Zstd)
+        );
+    }
+
+    #[test]
+    fn falls_back_to_identity() {
+        assert_eq!(
+            preferred_encoding("", &Encoding::ALL),
+            Some(Encoding::Identity)
+        );
+        assert_eq!(
+            preferred_encoding("compress", &[Encoding::Gzip]),
+            Some(Encoding::Identity)
+        );
+        assert_eq!(
+            preferred_encoding("br;q=0.1, identity;q=0.5", &Encoding::ALL),
+            Some(Encoding::Identity)
+        );
+    }
+
+    #[test]
+    fn wildcard() {
+        assert_eq!(
+            preferred_encoding("*", &Encoding::ALL),
+            Some(Encoding::Brotli)
+        );
+        assert_eq!(
+            preferred_encoding("br;q=0, *;q=0.5", &Encoding::ALL),
+            Some(Encoding::Zstd)
+        );
+        assert_eq!(preferred_encoding("*;q=0", &[Encoding::Gzip]), None);
+        assert_eq!(
+            preferred_encoding("gzip;q=0, identity;q=0", &[Encoding::Gzip]),
+            None
+        );
+        assert_eq!(
+            preferred_encoding("*;q=0, identity", &[Encoding::Gzip]),
+            Some(Encoding::Identity)
+        );
+    }
+
+    #[test]
+    fn only_compresses_compressible_types() {
+        assert_eq!(
+            select_encoding("application/json", "gzip", &Encoding::ALL),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(
+            select_encoding("image/jpeg", "gzip", &Encoding::ALL),
+            Some(Encoding::Identity)
+        );
+    }
+
+    #[test]
+    fn heuristics_only_widen_the_compressible_set() {
+        for content_type in ["application/json", "image/jpeg", "application/x-foo+zip"] {
+            assert_eq!(
+                select_encoding_with_heuristics(content_type, "gzip", &Encoding::ALL),
+                select_encoding(content_type, "gzip", &Encoding::ALL)
+            );
+        }
+        assert_eq!(
+            select_encoding("application/vnd.acme+json", "gzip", &Encoding::ALL),
+            Some(Encoding::Identity)
+        );
+        assert_eq!(
+            select_encoding_with_heuristics("text/x-unlisted", "*;q=0", &Encoding::ALL),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_tokens() {
+        assert_eq!("BR".parse(), Ok(Encoding::Brotli));
+        assert_eq!(" gzip ".parse(), Ok(Encoding::Gzip));
+        assert_eq!("compress".parse::<Encoding>(), Err(ParseEncodingError));
+        assert_eq!(Encoding::Zstd.to_string(), "zstd");
+    }
+}
@@ -0,0 +1,365 @@
+//! Deciding whether a particular response is worth compressing.
+//!
+//! ```
+//! use compressible::{CompressionPolicy, Decision, ResponseMeta, SkipReason};
+//!
+//! let policy = CompressionPolicy::new()
+//!     .min_size(1024)
+//!     .min_size_for("application/json", 256)?;
+//!
+//! let response = ResponseMeta {
+//!     content_type: Some("application/json"),
+//!     content_length: Some(40),
+//!     ..ResponseMeta::default()
+//! };
+//! assert_eq!(policy.decide(&response), Decision::Skip(SkipReason::TooSmall));
+//! # Ok::<(), compressible::InvalidPattern>(())
+//! ```
+use crate::essence::Essence;
+use crate::{
+    compressibility, compressibility_with_heuristics, Compressibility, CompressibleRegistry,
+    InvalidPattern,
+};
+
+/// What [`CompressionPolicy::decide`] needs to know about a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseMeta<'a> {
+    /// The request method. Defaults to `GET`.
+    pub method: &'a str,
+    /// The response status code. Defaults to `200`.
+    pub status: u16,
+    /// The `Content-Type` of the response, if any.
+    pub content_type: Option<&'a str>,
+    /// The size of the uncompressed body, if known up front.
+    pub content_length: Option<u64>,
+}
+
+impl Default for ResponseMeta<'_> {
+    fn default() -> Self {
+        ResponseMeta {
+            method: "GET",
+            status: 200,
+            content_type: None,
+            content_length: None,
+        }
+    }
+}
+
+/// The outcome of [`CompressionPolicy::decide`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Decision {
+    /// The response should be compressed.
+    Compress,
+    /// The response should be sent as is.
+    Skip(SkipReason),
+}
+
+impl Decision {
+    /// Returns `true` for `Decision::Compress`.
+    pub fn should_compress(self) -> bool {
+        self == Decision::Compress
+    }
+}
+
+/// Why [`CompressionPolicy::decide`] decided not to compress a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SkipReason {
+    /// The request was a `HEAD` request, so there is no body.
+    HeadRequest,
+    /// The status code has no body (1xx, 204, 304), is a partial response
+    /// (206) or was excluded with [`CompressionPolicy::skip_status`].
+    Status,
+    /// The response has no `Content-Type`.
+    MissingContentType,
+    /// The content type is known not to be compressible.
+    NotCompressible,
+    /// The content type is not in the dataset.
+    UnknownContentType,
+    /// The body is smaller than the minimum size for its type.
+    TooSmall,
+    /// The body is larger than the maximum size.
+    TooLarge,
+}
+
+/// Combines content type compressibility with size and status code rules.
+///
+/// By default bodies under 1 KiB, `HEAD` requests and `1xx`, `204`, `206` and
+/// `304` responses are skipped, and the content type is checked against the
+/// built-in dataset.
+#[derive(Debug, Clone)]
+pub struct CompressionPolicy {
+    min_size: u64,
+    max_size: Option<u64>,
+    type_min_sizes: Vec<(Box<str>, u64)>,
+    skip_statuses: Vec<u16>,
+    heuristics: bool,
+    registry: Option<CompressibleRegistry>,
+}
+
+impl Default for CompressionPolicy {
+    fn default() -> Self {
+        CompressionPolicy {
+            min_size: 1024,
+            max_size: None,
+            type_min_sizes: Vec::new(),
+            skip_statuses: vec![204, 206, 304],
+            heuristics: false,
+            registry: None,
+        }
+    }
+}
+
+impl CompressionPolicy {
+    /// Creates a policy with the defaults described on [`CompressionPolicy`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the smallest body, in bytes, that is worth compressing.
+    pub fn min_size(mut self, bytes: u64) -> Self {
+        self.min_size = bytes;
+        self
+    }
+
+    /// Sets the largest body, in bytes, that is compressed.
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Overrides the minimum size for a single `type/subtype`, matched
+    /// case-insensitively.
+    ///
+    /// Returns an error if `media_type` is malformed, has parameters or is a
+    /// wildcard.
+    pub fn min_size_for(mut self, media_type: &str, bytes: u64) -> Result<Self, InvalidPattern> {
+        let media_type: Box<str> = match Essence::parse(media_type) {
+            Some(essence)
+                if !media_type.contains(';')
+                    && essence.type_() != "*"
+                    && essence.subtype() != "*" =>
+            {
+                essence.as_str().into()
+            }
+            _ => return Err(InvalidPattern(media_type.to_owned())),
+        };
+        match self
+            .type_min_sizes
+            .binary_search_by(|(candidate, _)| candidate.cmp(&media_type))
+        {
+            Ok(index) => self.type_min_sizes[index].1 = bytes,
+            Err(index) => self.type_min_sizes.insert(index, (media_type, bytes)),
+        }
+        Ok(self)
+    }
+
+    /// Never compresses responses with this status code.
+    pub fn skip_status(mut self, status: u16) -> Self {
+        if !self.skip_statuses.contains(&status) {
+            self.skip_statuses.push(status);
+        }
+        self
+    }
+
+    /// Classifies types missing from the dataset with
+    /// [`compressibility_with_heuristics`].
+    pub fn heuristics(mut self, enabled: bool) -> Self {
+        self.heuristics = enabled;
+        self
+    }
+
+    /// Looks content types up in `registry` instead of the built-in dataset.
+    pub fn registry(mut self, registry: CompressibleRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Decides whether the described response should be compressed.
+    ///
+    /// A missing `content_length` passes the size checks, as the size of a
+    /// streamed body is not known up front.
+    pub fn decide(&self, response: &ResponseMeta<'_>) -> Decision {
+        if response.method.eq_ignore_ascii_case("HEAD") {
+            return Decision::Skip(SkipReason::HeadRequest);
+        }
+        if (100..200).contains(&response.status) || self.skip_statuses.contains(&response.status) {
+            return Decision::Skip(SkipReason::Status);
+        }
+
+        let content_type = match response.content_type {
+            Some(content_type) => content_type,
+            None => return Decision::Skip(SkipReason::MissingContentType),
+        };
+        match self.compressibility(content_type) {
+            Compressibility::Yes => {}
+            Compressibility::No => return Decision::Skip(SkipReason::NotCompressible),
+            Compressibility::Unknown => return Decision::Skip(SkipReason::UnknownContentType),
+        }
+
+        if let Some(length) = response.content_length {
+            if length < self.min_size_of(content_type) {
+                return Decision::Skip(SkipReason::TooSmall);
+            }
+            if self.max_size.is_some_and(|max_size| length > max_size) {
+                return Decision::Skip(SkipReason::TooLarge);
+            }
+        }
+
+        Decision::Compress
+    }
+
+    fn compressibility(&self, content_type: &str) -> Compressibility {
+        match (&self.registry, self.heuristics) {
+            (Some(registry), _) => registry.compressibility(content_type),
+            (None, true) => compressibility_with_heuristics(content_type),
+            (None, false) => compressibility(content_type),
+        }
+    }
+
+    fn min_size_of(&self, content_type: &str) -> u64 {
+        let essence = match Essence::parse(content_type) {
+            Some(essence) => essence,
+            None => return self.min_size,
+        };
+        self.type_min_sizes
+            .binary_search_by(|(candidate, _)| (**candidate).cmp(essence.as_str()))
+            .map_or(self.min_size, |index| self.type_min_sizes[index].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(content_type: &str, content_length: u64) -> ResponseMeta<'_> {
+        ResponseMeta {
+            content_type: Some(content_type),
+            content_length: Some(content_length),
+            ..ResponseMeta::default()
+        }
+    }
+
+    #[test]
+    fn compresses_large_compressible_bodies() {
+        let policy = CompressionPolicy::new();
+
+        assert_eq!(policy.decide(&meta("text/html", 4096)), Decision::Compress);
+        assert!(policy.decide(&meta("text/html", 4096)).should_compress());
+        assert_eq!(
+            policy.decide(&ResponseMeta {
+                content_type: Some("text/html"),
+                ..ResponseMeta::default()
+            }),
+            Decision::Compress
+        );
+    }
+
+    #[test]
+    fn reasons() {
+        let policy = CompressionPolicy::new().max_size(1 << 20);
+
+        assert_eq!(
+            policy.decide(&ResponseMeta {
+                method: "head",
+                ..meta("text/html", 4096)
+            }),
+            Decision::Skip(SkipReason::HeadRequest)
+        );
+        for &status in &[101, 204, 206, 304] {
+            assert_eq!(
+                policy.decide(&ResponseMeta {
+                    status,
+                    ..meta("text/html", 4096)
+                }),
+                Decision::Skip(SkipReason::Status)
+            );
+        }
+        assert_eq!(
+            policy.decide(&ResponseMeta::default()),
+            Decision::Skip(SkipReason::MissingContentType)
+        );
+        assert_eq!(
+            policy.decide(&meta("image/png", 4096)),
+            Decision::Skip(SkipReason::NotCompressible)
+        );
+        assert_eq!(
+            policy.decide(&meta("application/x-unknown", 4096)),
+            Decision::Skip(SkipReason::UnknownContentType)
+        );
+        assert_eq!(
+            policy.decide(&meta("text/html", 40)),
+            Decision::Skip(SkipReason::TooSmall)
+        );
+        assert_eq!(
+            policy.decide(&meta("text/html", 2 << 20)),
+            Decision::Skip(SkipReason::TooLarge)
+        );
+    }
+
+    #[test]
+    fn per_type_thresholds() {
+        let policy = CompressionPolicy::new()
+            .min_size_for("Application/JSON", 64)
+            .unwrap()
+            .min_size_for("application/json", 128)
+            .unwrap()
+            .skip_status(500);
+
+        assert_eq!(
+            policy.decide(&meta("application/json; charset=utf-8", 200)),
+            Decision::Compress
+        );
+        assert_eq!(
+            policy.decide(&meta("application/json", 100)),
+            Decision::Skip(SkipReason::TooSmall)
+        );
+        assert_eq!(
+            policy.decide(&ResponseMeta {
+                status: 500,
+                ..meta("application/json", 4096)
+            }),
+            Decision::Skip(SkipReason::Status)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_thresholds() {
+        for &media_type in &[
+            "json",
+            "application/",
+            "application/json; charset=utf-8",
+            "text/*",
+            "*/*",
+            "",
+        ] {
+            assert_eq!(
+                CompressionPolicy::new().min_size_for(media_type, 64).err(),
+                Some(InvalidPattern(media_type.to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn type_lookup() {
+        let unlisted = meta("application/vnd.acme+json", 4096);
+        let registry = CompressibleRegistry::builder()
+            .deny("text/html")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            CompressionPolicy::new().decide(&unlisted),
+            Decision::Skip(SkipReason::UnknownContentType)
+        );
+        assert_eq!(
+            CompressionPolicy::new().heuristics(true).decide(&unlisted),
+            Decision::Compress
+        );
+        assert_eq!(
+            CompressionPolicy::new()
+                .registry(registry)
+                .decide(&meta("text/html", 4096)),
+            Decision::Skip(SkipReason::NotCompressible)
+        );
+    }
+}
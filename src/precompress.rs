@@ -0,0 +1,255 @@
+//! Writing precompressed sidecar files for static sites.
+//!
+//! [`Precompressor::run`] walks a directory, selects files whose extension
+//! maps to a compressible type, and writes `.gz`, `.br` and `.zst` copies
+//! next to them, such as `app.js.br` for `app.js`.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::encode::{compress, is_enabled, Level};
+use crate::{extension_to_mime, is_extension_compressible, Encoding};
+
+/// Walks a directory and writes compressed sidecars for compressible files.
+#[derive(Debug, Clone)]
+pub struct Precompressor {
+    encodings: Vec<Encoding>,
+    level: Level,
+}
+
+impl Default for Precompressor {
+    fn default() -> Self {
+        Precompressor {
+            encodings: [Encoding::Gzip, Encoding::Brotli, Encoding::Zstd]
+                .iter()
+                .copied()
+                .filter(|&encoding| is_enabled(encoding))
+                .collect(),
+            level: Level::Best,
+        }
+    }
+}
+
+impl Precompressor {
+    /// Creates a precompressor writing gzip, brotli and zstd sidecars at
+    /// `Level::Best`, for whichever of those codecs are enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the encodings to write sidecars for.
+    ///
+    /// Encodings without a [`Encoding::file_extension`] are ignored. Running
+    /// with an encoding whose codec is disabled fails, see
+    /// [`crate::encode`].
+    pub fn encodings(mut self, encodings: &[Encoding]) -> Self {
+        self.encodings = encodings
+            .iter()
+            .copied()
+            .filter(|encoding| encoding.file_extension().is_some())
+            .collect();
+        self
+    }
+
+    /// Sets the compression level. Defaults to `Level::Best`.
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Writes sidecars for every compressible file under `root` and returns a
+    /// manifest of what was written.
+    ///
+    /// A sidecar that is not smaller than the original is not written, and a
+    /// stale one left over from an earlier run is removed. Existing sidecars
+    /// are never treated as inputs, and symbolic links are not followed.
+    pub fn run(&self, root: &Path) -> io::Result<Manifest> {
+        let mut files = Vec::new();
+        walk(root, &mut files)?;
+        files.sort();
+
+        let mut manifest = Manifest::default();
+        for path in files {
+            let extension = match path.extension().and_then(|extension| extension.to_str()) {
+                Some(extension) if is_extension_compressible(extension) => extension,
+                _ => continue,
+            };
+            let content_type =
+                extension_to_mime(extension).expect("compressible extensions have a type");
+
+            let data = fs::read(&path)?;
+            let mut sidecars = Vec::new();
+            for &encoding in &self.encodings {
+                let sidecar_path = sidecar(&path, encoding);
+                let compressed = compress(encoding, self.level, &data)?;
+
+                if compressed.len() < data.len() {
+                    fs::write(&sidecar_path, &compressed)?;
+                    sidecars.push(Sidecar {
+                        encoding,
+                        path: relative(root, &sidecar_path),
+                        size: compressed.len() as u64,
+                    });
+                } else if sidecar_path.exists() {
+                    fs::remove_file(&sidecar_path)?;
+                }
+            }
+
+            manifest.files.push(ManifestEntry {
+                path: relative(root, &path),
+                content_type,
+                size: data.len() as u64,
+                sidecars,
+            });
+        }
+        Ok(manifest)
+    }
+}
+
+/// Collects regular files under `dir`, skipping precompressed sidecars.
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+
+        if file_type.is_dir() {
+            walk(&path, files)?;
+        } else if file_type.is_file() && !is_sidecar(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_sidecar(path: &Path) -> bool {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    Encoding::ALL
+        .iter()
+        .filter_map(|encoding| encoding.file_extension())
+        .any(|sidecar| Some(sidecar) == extension)
+}
+
+fn sidecar(path: &Path, encoding: Encoding) -> PathBuf {
+    let extension = encoding
+        .file_extension()
+        .expect("sidecar encodings have an extension");
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".");
+    sidecar.push(extension);
+    PathBuf::from(sidecar)
+}
+
+fn relative(root: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
+
+/// The files processed by [`Precompressor::run`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// Every compressible file found, sorted by path.
+    pub files: Vec<ManifestEntry>,
+}
+
+/// A compressible file and the sidecars written for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Path of the original file, relative to the root.
+    pub path: PathBuf,
+    /// The media type mapped from the file extension.
+    pub content_type: &'static str,
+    /// Size of the original file in bytes.
+    pub size: u64,
+    /// Sidecars that came out smaller than the original.
+    pub sidecars: Vec<Sidecar>,
+}
+
+/// A precompressed copy of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sidecar {
+    /// The encoding of the sidecar, used as its `Content-Encoding`.
+    pub encoding: Encoding,
+    /// Path of the sidecar, relative to the root.
+    pub path: PathBuf,
+    /// Size of the sidecar in bytes.
+    pub size: u64,
+}
+
+#[cfg(all(test, feature = "gzip"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_smaller_sidecars() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("css")).unwrap();
+
+        let css = "body { margin: 0; padding: 0; }\n".repeat(100);
+        fs::write(root.join("css/site.css"), &css).unwrap();
+        fs::write(root.join("tiny.txt"), "a").unwrap();
+        fs::write(root.join("tiny.txt.gz"), "stale").unwrap();
+        fs::write(root.join("logo.png"), [0x89, b'P', b'N', b'G']).unwrap();
+
+        let manifest = Precompressor::new()
+            .encodings(&[Encoding::Gzip, Encoding::Deflate])
+            .run(root)
+            .unwrap();
+
+        assert_eq!(manifest.files.len(), 2);
+        let site = &manifest.files[0];
+        assert_eq!(site.path, Path::new("css/site.css"));
+        assert_eq!(site.content_type, "text/css");
+        assert_eq!(site.size, css.len() as u64);
+        assert_eq!(site.sidecars.len(), 1);
+        assert_eq!(site.sidecars[0].encoding, Encoding::Gzip);
+        assert_eq!(site.sidecars[0].path, Path::new("css/site.css.gz"));
+        assert!(root.join("css/site.css.gz").exists());
+
+        let tiny = &manifest.files[1];
+        assert_eq!(tiny.path, Path::new("tiny.txt"));
+        assert!(tiny.sidecars.is_empty());
+        assert!(!root.join("tiny.txt.gz").exists());
+        assert!(!root.join("logo.png.gz").exists());
+
+        // Sidecars from the first run are not picked up as inputs.
+        assert_eq!(
+            Precompressor::new()
+                .encodings(&[Encoding::Gzip])
+                .run(root)
+                .unwrap(),
+            manifest
+        );
+    }
+
+    #[test]
+    fn defaults_to_enabled_codecs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let css = "body { margin: 0; padding: 0; }\n".repeat(100);
+        fs::write(root.join("site.css"), &css).unwrap();
+
+        let manifest = Precompressor::new().run(root).unwrap();
+
+        let written: Vec<_> = manifest.files[0]
+            .sidecars
+            .iter()
+            .map(|sidecar| sidecar.encoding)
+            .collect();
+        let mut expected = vec![Encoding::Gzip];
+        if cfg!(feature = "brotli") {
+            expected.push(Encoding::Brotli);
+        }
+        if cfg!(feature = "zstd") {
+            expected.push(Encoding::Zstd);
+        }
+        assert_eq!(written, expected);
+
+        // Sidecars are compressed at `Level::Best` but must stay servable.
+        #[cfg(feature = "zstd")]
+        {
+            let zst = fs::read(root.join("site.css.zst")).unwrap();
+            assert!(crate::encode::zstd_window_size(&zst) <= 8 << 20);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! A [`tower_http`] compression predicate backed by the dataset.
+use http::Response;
+use http_body::Body;
+use tower_http::compression::predicate::Predicate;
+
+use crate::should_compress_response;
+
+/// A [`Predicate`] for `tower_http::compression::CompressionLayer` that
+/// compresses responses whose `Content-Type` is compressible according to
+/// mime-db.
+///
+/// Responses that are already encoded or marked `Cache-Control: no-transform`
+/// are left alone, see [`should_compress_response`]. Combine it with other
+/// predicates such as a size threshold using [`Predicate::and`]:
+///
+/// ```
+/// use compressible::MimeDbPredicate;
+/// use tower_http::compression::predicate::{Predicate, SizeAbove};
+/// use tower_http::compression::CompressionLayer;
+///
+/// let layer = CompressionLayer::new().compress_when(MimeDbPredicate::new().and(SizeAbove::new(256)));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MimeDbPredicate {
+    _priv: (),
+}
+
+impl MimeDbPredicate {
+    /// Creates a new `MimeDbPredicate`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Predicate for MimeDbPredicate {
+    fn should_compress<B>(&self, response: &Response<B>) -> bool
+    where
+        B: Body,
+    {
+        should_compress_response(response.headers())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE};
+    use http::{Request, Response};
+    use http_body_util::Full;
+    use tower::{service_fn, ServiceBuilder, ServiceExt};
+    use tower_http::compression::predicate::{Predicate, SizeAbove};
+    use tower_http::compression::CompressionLayer;
+
+    use super::MimeDbPredicate;
+
+    /// Runs a request through an in-process service that responds with
+    /// `body` labelled as `content_type`, and returns the response encoding.
+    async fn encoding<P>(
+        predicate: P,
+        content_type: &'static str,
+        body: &'static str,
+    ) -> Option<String>
+    where
+        P: Predicate,
+    {
+        let service = ServiceBuilder::new()
+            .layer(CompressionLayer::new().compress_when(predicate))
+            .service(service_fn(
+                move |_: Request<Full<&'static [u8]>>| async move {
+                    let response = Response::builder()
+                        .header(CONTENT_TYPE, content_type)
+                        .body(Full::new(body.as_bytes()))
+                        .unwrap();
+                    Ok::<_, Infallible>(response)
+                },
+            ));
+
+        let request = Request::builder()
+            .header(ACCEPT_ENCODING, "gzip")
+            .body(Full::default())
+            .unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        response
+            .headers()
+            .get(CONTENT_ENCODING)
+            .map(|value| value.to_str().unwrap().to_owned())
+    }
+
+    #[tokio::test]
+    async fn compresses_by_content_type() {
+        let body = "a fairly long body that is worth compressing, repeated a few times";
+
+        assert_eq!(
+            encoding(MimeDbPredicate::new(), "application/vnd.api+json", body).await,
+            Some("gzip".to_owned())
+        );
+        assert_eq!(
+            encoding(MimeDbPredicate::new(), "image/jpeg", body).await,
+            None
+        );
+        assert_eq!(
+            encoding(MimeDbPredicate::new(), "application/x-unknown", body).await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn combines_with_size_threshold() {
+        let predicate = MimeDbPredicate::new().and(SizeAbove::new(32));
+
+        assert_eq!(encoding(predicate, "text/css", "body{}").await, None);
+        assert_eq!(
+            encoding(
+                predicate,
+                "text/css",
+                "body { margin: 0; padding: 0; color: black; }"
+            )
+            .await,
+            Some("gzip".to_owned())
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Enumerating the dataset, e.g. to generate web server configuration.
+//!
+//! ```
+//! use compressible::compressible_types_with_suffix;
+//!
+//! let json: Vec<_> = compressible_types_with_suffix("json").collect();
+//! assert!(json.contains(&"application/ld+json"));
+//! ```
+use crate::essence::{cmp_ignore_ascii_case, suffix};
+use crate::{CONTENT_TYPES, INCOMPRESSIBLE_TYPES};
+
+/// Returns every media type the dataset marks as compressible, sorted.
+pub fn compressible_types() -> impl Iterator<Item = &'static str> {
+    CONTENT_TYPES.iter().copied()
+}
+
+/// Returns every media type the dataset explicitly marks as not
+/// compressible, sorted.
+pub fn incompressible_types() -> impl Iterator<Item = &'static str> {
+    INCOMPRESSIBLE_TYPES.iter().copied()
+}
+
+/// Returns the compressible media types starting with `prefix`, matched
+/// case-insensitively, e.g. `text/` or `application/vnd.`.
+///
+/// ```
+/// use compressible::compressible_types_with_prefix;
+///
+/// assert!(compressible_types_with_prefix("text/").all(|t| t.starts_with("text/")));
+/// ```
+pub fn compressible_types_with_prefix<'a>(
+    prefix: &'a str,
+) -> impl Iterator<Item = &'static str> + 'a {
+    let start = CONTENT_TYPES.partition_point(|candidate| {
+        cmp_ignore_ascii_case(candidate, prefix) == core::cmp::Ordering::Less
+    });
+    CONTENT_TYPES[start..]
+        .iter()
+        .copied()
+        .take_while(move |candidate| {
+            candidate.len() >= prefix.len()
+                && candidate.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+        })
+}
+
+/// Returns the compressible media types with the structured syntax suffix,
+/// given without the leading `+` and matched case-insensitively.
+pub fn compressible_types_with_suffix<'a>(
+    suffix: &'a str,
+) -> impl Iterator<Item = &'static str> + 'a {
+    compressible_types().filter(move |candidate| {
+        structured_suffix(candidate).is_some_and(|found| found.eq_ignore_ascii_case(suffix))
+    })
+}
+
+fn structured_suffix(essence: &str) -> Option<&str> {
+    essence
+        .split_once('/')
+        .and_then(|(_, subtype)| suffix(subtype))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compressibility, Compressibility};
+
+    #[test]
+    fn enumerates_the_dataset() {
+        assert_eq!(compressible_types().count(), CONTENT_TYPES.len());
+        assert!(compressible_types().all(|t| compressibility(t) == Compressibility::Yes));
+        assert!(incompressible_types().all(|t| compressibility(t) == Compressibility::No));
+    }
+
+    #[test]
+    fn prefix_queries() {
+        let text: Vec<_> = compressible_types_with_prefix("TEXT/").collect();
+        assert!(text.contains(&"text/html"));
+        assert!(text.contains(&"text/css"));
+        assert_eq!(
+            text.len(),
+            compressible_types()
+                .filter(|t| t.starts_with("text/"))
+                .count()
+        );
+
+        assert_eq!(
+            compressible_types_with_prefix("").count(),
+            CONTENT_TYPES.len()
+        );
+        assert_eq!(compressible_types_with_prefix("zzz").count(), 0);
+        assert_eq!(
+            compressible_types_with_prefix("application/json").next(),
+            Some("application/json")
+        );
+    }
+
+    #[test]
+    fn suffix_queries() {
+        let json: Vec<_> = compressible_types_with_suffix("JSON").collect();
+        assert!(json.contains(&"application/ld+json"));
+        assert!(json.contains(&"application/vnd.api+json"));
+        assert!(!json.contains(&"application/json"));
+        assert!(json.iter().all(|t| t.ends_with("+json")));
+
+        assert!(compressible_types_with_suffix("xml").any(|t| t == "image/svg+xml"));
+        assert_eq!(compressible_types_with_suffix("zip").count(), 0);
+    }
+}
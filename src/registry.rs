@@ -0,0 +1,261 @@
+//! A registry seeded from the built-in dataset that accepts overrides.
+//!
+//! ```
+//! use compressible::{CompressibleRegistry, Compressibility};
+//!
+//! let registry = CompressibleRegistry::builder()
+//!     .allow("application/x-ndjson-internal")
+//!     .deny("image/svg+xml")
+//!     .allow_suffix("json")
+//!     .build()
+//!     .unwrap();
+//!
+//! assert!(registry.is_compressible("application/x-ndjson-internal"));
+//! assert!(registry.is_compressible("application/vnd.acme+json"));
+//! assert!(!registry.is_compressible("image/svg+xml"));
+//! assert_eq!(registry.compressibility("image/jpeg"), Compressibility::No);
+//! ```
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "mime")]
+use mime::Mime;
+
+use crate::essence::Essence;
+use crate::{essence_compressibility, Compressibility};
+
+/// Compressibility lookups with user supplied overrides on top of the
+/// built-in dataset.
+///
+/// Rules are consulted from most to least specific: exact media types, then
+/// structured syntax suffixes, then `type/*` wildcards, and finally the
+/// built-in dataset. Create one with [`CompressibleRegistry::builder`].
+#[derive(Debug, Clone)]
+pub struct CompressibleRegistry {
+    exact: Vec<(Box<str>, bool)>,
+    suffixes: Vec<(Box<str>, bool)>,
+    wildcards: Vec<(Box<str>, bool)>,
+}
+
+impl CompressibleRegistry {
+    /// Returns a builder seeded with the built-in dataset.
+    pub fn builder() -> CompressibleRegistryBuilder {
+        CompressibleRegistryBuilder::default()
+    }
+
+    /// Returns the [`Compressibility`] of the provided content type, or
+    /// `Compressibility::Unknown` if it cannot be parsed.
+    pub fn compressibility(&self, content_type: &str) -> Compressibility {
+        let essence = match Essence::parse(content_type) {
+            Some(essence) => essence,
+            None => return Compressibility::Unknown,
+        };
+
+        let rule = find(&self.exact, essence.as_str())
+            .or_else(|| {
+                essence
+                    .suffix()
+                    .and_then(|suffix| find(&self.suffixes, suffix))
+            })
+            .or_else(|| find(&self.wildcards, essence.type_()));
+
+        match rule {
+            Some(true) => Compressibility::Yes,
+            Some(false) => Compressibility::No,
+            None => essence_compressibility(essence.as_str()),
+        }
+    }
+
+    /// Returns the [`Compressibility`] of an already parsed [`Mime`].
+    #[cfg(feature = "mime")]
+    pub fn mime_compressibility(&self, mime: &Mime) -> Compressibility {
+        self.compressibility(mime.essence_str())
+    }
+
+    /// Returns `bool` indicating whether the provided content type is
+    /// compressible.
+    pub fn is_compressible(&self, content_type: &str) -> bool {
+        self.compressibility(content_type) == Compressibility::Yes
+    }
+}
+
+fn find(rules: &[(Box<str>, bool)], key: &str) -> Option<bool> {
+    rules
+        .binary_search_by(|(candidate, _)| (**candidate).cmp(key))
+        .ok()
+        .map(|index| rules[index].1)
+}
+
+/// Builder for [`CompressibleRegistry`].
+///
+/// When several rules target the same pattern, the one added last wins.
+#[derive(Debug, Clone, Default)]
+pub struct CompressibleRegistryBuilder {
+    rules: Vec<(Pattern, bool)>,
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    MediaType(String),
+    Suffix(String),
+}
+
+impl CompressibleRegistryBuilder {
+    /// Marks a media type, or every subtype of a `type/*` wildcard, as
+    /// compressible.
+    pub fn allow(self, pattern: &str) -> Self {
+        self.rule(Pattern::MediaType(pattern.to_owned()), true)
+    }
+
+    /// Marks a media type, or every subtype of a `type/*` wildcard, as not
+    /// compressible.
+    pub fn deny(self, pattern: &str) -> Self {
+        self.rule(Pattern::MediaType(pattern.to_owned()), false)
+    }
+
+    /// Marks every media type with the structured syntax suffix, given
+    /// without the leading `+`, as compressible.
+    pub fn allow_suffix(self, suffix: &str) -> Self {
+        self.rule(Pattern::Suffix(suffix.to_owned()), true)
+    }
+
+    /// Marks every media type with the structured syntax suffix, given
+    /// without the leading `+`, as not compressible.
+    pub fn deny_suffix(self, suffix: &str) -> Self {
+        self.rule(Pattern::Suffix(suffix.to_owned()), false)
+    }
+
+    fn rule(mut self, pattern: Pattern, compressible: bool) -> Self {
+        self.rules.push((pattern, compressible));
+        self
+    }
+
+    /// Validates the rules and builds an immutable [`CompressibleRegistry`].
+    pub fn build(self) -> Result<CompressibleRegistry, InvalidPattern> {
+        let mut exact = Vec::new();
+        let mut suffixes = Vec::new();
+        let mut wildcards = Vec::new();
+
+        for (pattern, compressible) in self.rules {
+            match pattern {
+                Pattern::MediaType(pattern) => {
+                    let essence = match Essence::parse(&pattern) {
+                        Some(essence) if !pattern.contains(';') => essence,
+                        _ => return Err(InvalidPattern(pattern)),
+                    };
+                    if essence.subtype() == "*" {
+                        if essence.type_() == "*" {
+                            return Err(InvalidPattern(pattern));
+                        }
+                        wildcards.push((essence.type_().into(), compressible));
+                    } else {
+                        exact.push((essence.as_str().into(), compressible));
+                    }
+                }
+                Pattern::Suffix(suffix) => {
+                    let valid = !suffix.is_empty()
+                        && suffix
+                            .bytes()
+                            .all(|b| b.is_ascii_alphanumeric() || b"!#$&-^_.".contains(&b));
+                    if !valid {
+                        return Err(InvalidPattern(suffix));
+                    }
+                    suffixes.push((suffix.to_ascii_lowercase().into(), compressible));
+                }
+            }
+        }
+
+        Ok(CompressibleRegistry {
+            exact: normalize(exact),
+            suffixes: normalize(suffixes),
+            wildcards: normalize(wildcards),
+        })
+    }
+}
+
+/// Sorts rules for binary search, keeping only the last rule for each key.
+fn normalize(mut rules: Vec<(Box<str>, bool)>) -> Vec<(Box<str>, bool)> {
+    rules.reverse();
+    rules.sort_by(|a, b| a.0.cmp(&b.0));
+    rules.dedup_by(|a, b| a.0 == b.0);
+    rules
+}
+
+/// Error returned by [`CompressibleRegistryBuilder::build`] for a pattern that
+/// is neither a media type, a `type/*` wildcard nor a suffix, and by
+/// [`CompressionPolicy::min_size_for`](crate::CompressionPolicy::min_size_for)
+/// for anything but a media type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPattern(pub(crate) String);
+
+impl fmt::Display for InvalidPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid media type pattern `{}`", self.0)
+    }
+}
+
+impl Error for InvalidPattern {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compressibility;
+
+    #[test]
+    fn defaults_to_dataset() {
+        let registry = CompressibleRegistry::builder().build().unwrap();
+
+        for content_type in &["text/html", "image/png", "application/x-unknown", "garbage"] {
+            assert_eq!(
+                registry.compressibility(content_type),
+                compressibility(content_type)
+            );
+        }
+    }
+
+    #[test]
+    fn specific_rules_win() {
+        let registry = CompressibleRegistry::builder()
+            .deny("text/*")
+            .allow("TEXT/Plain")
+            .deny_suffix("xml")
+            .allow("image/*")
+            .deny("image/gif")
+            .build()
+            .unwrap();
+
+        assert!(registry.is_compressible("text/plain; charset=utf-8"));
+        assert!(!registry.is_compressible("text/html"));
+        assert!(!registry.is_compressible("text/vnd.foo+xml"));
+        assert!(!registry.is_compressible("application/atom+xml"));
+        assert!(registry.is_compressible("image/jpeg"));
+        assert!(!registry.is_compressible("image/gif"));
+        assert!(registry.is_compressible("application/json"));
+    }
+
+    #[test]
+    fn last_rule_wins() {
+        let registry = CompressibleRegistry::builder()
+            .allow("image/png")
+            .deny("image/png")
+            .build()
+            .unwrap();
+
+        assert_eq!(registry.compressibility("image/png"), Compressibility::No);
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        for pattern in &["text", "*/*", "text/html; charset=utf-8", "text/html;", ""] {
+            let err = CompressibleRegistry::builder()
+                .allow(pattern)
+                .build()
+                .unwrap_err();
+            assert_eq!(err, InvalidPattern((*pattern).to_owned()));
+        }
+        assert!(CompressibleRegistry::builder()
+            .allow_suffix("+json")
+            .build()
+            .is_err());
+    }
+}
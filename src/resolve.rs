@@ -0,0 +1,220 @@
+//! Serving precompressed sidecars from a static file root.
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::negotiate::preferred_encoding;
+use crate::{extension_to_mime, Encoding};
+
+/// Picks the best precompressed sidecar for a request, such as `app.js.br`
+/// for `/app.js`, as written by `compressible precompress`.
+///
+/// ```no_run
+/// use compressible::SidecarResolver;
+///
+/// let resolver = SidecarResolver::new("/srv/www");
+/// if let Some(file) = resolver.resolve("/assets/app.js", "gzip, br") {
+///     // Serve `file.path` with `Content-Type: file.content_type`,
+///     // `Content-Encoding: file.encoding` unless it is identity, and
+///     // `Vary: Accept-Encoding`.
+///     assert_eq!(file.content_type, Some("application/javascript"));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SidecarResolver {
+    root: PathBuf,
+    encodings: Vec<Encoding>,
+}
+
+/// A file picked by [`SidecarResolver::resolve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedFile {
+    /// The file to send, either a sidecar or the original file.
+    pub path: PathBuf,
+    /// The `Content-Encoding` of `path`, `Encoding::Identity` for the
+    /// original file.
+    pub encoding: Encoding,
+    /// The media type of the original file, from its extension.
+    pub content_type: Option<&'static str>,
+}
+
+impl SidecarResolver {
+    /// Creates a resolver serving files under `root`, looking for brotli,
+    /// zstd and gzip sidecars in that order of preference.
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        SidecarResolver {
+            root: root.into(),
+            encodings: vec![Encoding::Brotli, Encoding::Zstd, Encoding::Gzip],
+        }
+    }
+
+    /// Sets which sidecar encodings to look for, in order of preference.
+    pub fn encodings(mut self, encodings: &[Encoding]) -> Self {
+        self.encodings = encodings.to_vec();
+        self
+    }
+
+    /// Resolves a request path, already percent-decoded and without a query
+    /// string, against the root.
+    ///
+    /// Returns the sidecar the client prefers according to its
+    /// `Accept-Encoding` header value, falling back to the original file.
+    /// Returns `None` if the original file does not exist, is not a regular
+    /// file, or the path tries to escape the root, e.g. with `..`.
+    pub fn resolve(&self, request_path: &str, accept_encoding: &str) -> Option<ResolvedFile> {
+        let original = self.join(request_path)?;
+        let root = fs::canonicalize(&self.root).ok()?;
+        if !is_file_within(&root, &original) {
+            return None;
+        }
+
+        let sidecars: Vec<(Encoding, PathBuf)> = self
+            .encodings
+            .iter()
+            .filter_map(|&encoding| {
+                let extension = encoding.file_extension()?;
+                let mut path = original.clone().into_os_string();
+                path.push(".");
+                path.push(extension);
+                let path = PathBuf::from(path);
+                is_file_within(&root, &path).then_some((encoding, path))
+            })
+            .collect();
+
+        let available: Vec<Encoding> = sidecars.iter().map(|(encoding, _)| *encoding).collect();
+        let content_type = original
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(extension_to_mime);
+
+        let chosen = preferred_encoding(accept_encoding, &available).and_then(|encoding| {
+            sidecars
+                .into_iter()
+                .find(|(candidate, _)| *candidate == encoding)
+        });
+        let (encoding, path) = chosen.unwrap_or((Encoding::Identity, original));
+
+        Some(ResolvedFile {
+            path,
+            encoding,
+            content_type,
+        })
+    }
+
+    /// Joins the normal components of `request_path` onto the root,
+    /// rejecting anything that could escape it.
+    fn join(&self, request_path: &str) -> Option<PathBuf> {
+        let mut path = self.root.clone();
+        let mut components = 0;
+
+        for segment in request_path.split('/') {
+            if segment.is_empty() || segment == "." {
+                continue;
+            }
+            if segment.contains('\\') || segment.contains('\0') {
+                return None;
+            }
+            match Path::new(segment).components().next() {
+                Some(Component::Normal(_)) => path.push(segment),
+                _ => return None,
+            }
+            components += 1;
+        }
+
+        if components == 0 {
+            None
+        } else {
+            Some(path)
+        }
+    }
+}
+
+/// Returns `true` if `path` is a regular file whose real location, after
+/// resolving symbolic links, is inside `root`.
+fn is_file_within(root: &Path, path: &Path) -> bool {
+    match fs::canonicalize(path) {
+        Ok(real) => real.starts_with(root) && real.is_file(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn fixture() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("www/js")).unwrap();
+        fs::write(root.join("www/js/app.js"), "js").unwrap();
+        fs::write(root.join("www/js/app.js.gz"), "gz").unwrap();
+        fs::write(root.join("www/js/app.js.br"), "br").unwrap();
+        fs::write(root.join("www/index.html"), "html").unwrap();
+        fs::write(root.join("secret.txt"), "secret").unwrap();
+        dir
+    }
+
+    #[test]
+    fn picks_preferred_sidecar() {
+        let dir = fixture();
+        let root = dir.path();
+        let resolver = SidecarResolver::new(root.join("www"));
+
+        let file = resolver.resolve("/js/app.js", "gzip, br").unwrap();
+        assert_eq!(file.encoding, Encoding::Brotli);
+        assert!(file.path.ends_with("js/app.js.br"));
+        assert_eq!(file.content_type, Some("application/javascript"));
+
+        let file = resolver
+            .resolve("/js/app.js", "gzip;q=1, br;q=0.5")
+            .unwrap();
+        assert_eq!(file.encoding, Encoding::Gzip);
+        assert!(file.path.ends_with("js/app.js.gz"));
+    }
+
+    #[test]
+    fn falls_back_to_original() {
+        let dir = fixture();
+        let root = dir.path();
+        let resolver = SidecarResolver::new(root.join("www"));
+
+        let file = resolver.resolve("/js/app.js", "zstd").unwrap();
+        assert_eq!(file.encoding, Encoding::Identity);
+        assert!(file.path.ends_with("js/app.js"));
+
+        let file = resolver.resolve("index.html", "br").unwrap();
+        assert_eq!(file.encoding, Encoding::Identity);
+        assert_eq!(file.content_type, Some("text/html"));
+
+        let file = resolver
+            .resolve("/js/app.js", "br;q=0, gzip;q=0, identity;q=0")
+            .unwrap();
+        assert_eq!(file.encoding, Encoding::Identity);
+
+        assert_eq!(resolver.resolve("/missing.css", "br"), None);
+        assert_eq!(resolver.resolve("/js", "br"), None);
+        assert_eq!(resolver.resolve("/", "br"), None);
+    }
+
+    #[test]
+    fn rejects_traversal() {
+        let dir = fixture();
+        let root = dir.path();
+        let resolver = SidecarResolver::new(root.join("www"));
+
+        assert_eq!(resolver.resolve("/../secret.txt", ""), None);
+        assert_eq!(resolver.resolve("/js/../../secret.txt", ""), None);
+        assert_eq!(resolver.resolve("/..\\secret.txt", ""), None);
+        assert_eq!(
+            resolver.resolve(&root.join("secret.txt").to_string_lossy(), ""),
+            None
+        );
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("secret.txt"), root.join("www/link.txt")).unwrap();
+            assert_eq!(resolver.resolve("/link.txt", ""), None);
+        }
+    }
+}
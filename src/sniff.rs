@@ -0,0 +1,170 @@
+//! Content sniffing for bodies with a missing or generic content type.
+use crate::Compressibility;
+
+/// Leading bytes of formats that are already compressed.
+static SIGNATURES: &[&[u8]] = &[
+    // PNG
+    b"\x89PNG\r\n\x1A\n",
+    // JPEG
+    b"\xFF\xD8\xFF",
+    // GIF
+    b"GIF87a",
+    b"GIF89a",
+    // gzip
+    b"\x1F\x8B\x08",
+    // zip, including formats built on it such as docx, jar and epub
+    b"PK\x03\x04",
+    // zstd
+    b"\x28\xB5\x2F\xFD",
+    // xz
+    b"\xFD7zXZ\x00",
+    // bzip2
+    b"BZh",
+    // 7z
+    b"7z\xBC\xAF\x27\x1C",
+    // RAR
+    b"Rar!\x1A\x07",
+    // PDF
+    b"%PDF-",
+    // WOFF and WOFF2
+    b"wOFF",
+    b"wOF2",
+    // Ogg
+    b"OggS\x00",
+    // WebM and Matroska
+    b"\x1A\x45\xDF\xA3",
+    // MP3 with an ID3 tag
+    b"ID3",
+];
+
+/// Returns the [`Compressibility`] of a body judging by its leading bytes,
+/// for use when the declared content type is missing or generic, such as
+/// `application/octet-stream`.
+///
+/// Formats that are already compressed (PNG, JPEG, GIF, WebP, MP4, gzip,
+/// zip, zstd, PDF, ...) are recognised by their magic numbers and return
+/// `Compressibility::No`. Bodies that look like text, following the WHATWG
+/// MIME Sniffing rules for binary data bytes and also requiring valid UTF-8
+/// unless a byte order mark is present, return `Compressibility::Yes`.
+/// Anything else is `Compressibility::Unknown`.
+///
+/// Only the first 512 bytes are inspected, as in the WHATWG algorithm.
+///
+/// ```
+/// use compressible::{sniff_compressible, Compressibility};
+///
+/// assert_eq!(sniff_compressible(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Compressibility::No);
+/// assert_eq!(sniff_compressible(b"{\"hello\": \"world\"}"), Compressibility::Yes);
+/// assert_eq!(sniff_compressible(&[0, 1, 2, 3]), Compressibility::Unknown);
+/// ```
+pub fn sniff_compressible(body: &[u8]) -> Compressibility {
+    let header = &body[..body.len().min(512)];
+
+    if header.is_empty() {
+        Compressibility::Unknown
+    } else if is_compressed(header) {
+        Compressibility::No
+    } else if is_text(header) {
+        Compressibility::Yes
+    } else {
+        Compressibility::Unknown
+    }
+}
+
+fn is_compressed(header: &[u8]) -> bool {
+    SIGNATURES
+        .iter()
+        .any(|signature| header.starts_with(signature))
+        || is_webp(header)
+        || is_mp4(header)
+}
+
+/// `RIFF`, a four byte size, then `WEBPVP`.
+fn is_webp(header: &[u8]) -> bool {
+    header.starts_with(b"RIFF") && header.get(8..14) == Some(&b"WEBPVP"[..])
+}
+
+/// The WHATWG "matches the signature for MP4" algorithm.
+fn is_mp4(header: &[u8]) -> bool {
+    if header.len() < 12 {
+        return false;
+    }
+
+    let box_size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    if header.len() < box_size || box_size % 4 != 0 || &header[4..8] != b"ftyp" {
+        return false;
+    }
+
+    if &header[8..11] == b"mp4" {
+        return true;
+    }
+    (16..box_size)
+        .step_by(4)
+        .any(|offset| header.get(offset..offset + 3) == Some(&b"mp4"[..]))
+}
+
+fn is_text(header: &[u8]) -> bool {
+    // UTF-16 and UTF-8 byte order marks.
+    if header.starts_with(&[0xFE, 0xFF])
+        || header.starts_with(&[0xFF, 0xFE])
+        || header.starts_with(&[0xEF, 0xBB, 0xBF])
+    {
+        return true;
+    }
+
+    let binary = header
+        .iter()
+        .any(|&b| matches!(b, 0x00..=0x08 | 0x0B | 0x0E..=0x1A | 0x1C..=0x1F));
+    if binary {
+        return false;
+    }
+
+    // Tolerate a multi-byte sequence cut off by the 512 byte limit.
+    match core::str::from_utf8(header) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Compressibility::*;
+
+    #[test]
+    fn compressed_formats() {
+        assert_eq!(sniff_compressible(&[0xFF, 0xD8, 0xFF, 0xE0, 0, 0x10]), No);
+        assert_eq!(sniff_compressible(b"GIF89a\x01\x00"), No);
+        assert_eq!(sniff_compressible(b"RIFF\x24\0\0\0WEBPVP8 "), No);
+        assert_eq!(sniff_compressible(b"Rar!\x1A\x07\x01\x00"), No);
+        assert_eq!(sniff_compressible(b"\x1f\x8b\x08\0\0\0\0\0"), No);
+        assert_eq!(sniff_compressible(b"PK\x03\x04\x14\0"), No);
+        assert_eq!(sniff_compressible(b"\x28\xb5\x2f\xfd\x00"), No);
+        assert_eq!(sniff_compressible(b"%PDF-1.7\n"), No);
+        assert_eq!(
+            sniff_compressible(b"\0\0\0\x18ftypisom\0\0\x02\0isommp41"),
+            No
+        );
+        assert_eq!(sniff_compressible(b"\0\0\0\x10ftypmp42\0\0\0\0"), No);
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(sniff_compressible(b"<!DOCTYPE html><html></html>"), Yes);
+        assert_eq!(sniff_compressible("naïve café\r\n\tüber".as_bytes()), Yes);
+        assert_eq!(sniff_compressible(b"\xEF\xBB\xBFhello"), Yes);
+        assert_eq!(sniff_compressible(b"\xFF\xFEh\0i\0"), Yes);
+
+        let mut cut = "a".repeat(511).into_bytes();
+        cut.extend_from_slice("é".as_bytes());
+        assert_eq!(sniff_compressible(&cut), Yes);
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(sniff_compressible(b""), Unknown);
+        assert_eq!(sniff_compressible(b"text with a \0 byte"), Unknown);
+        assert_eq!(sniff_compressible(b"invalid \xC3\x28 utf-8"), Unknown);
+        assert_eq!(sniff_compressible(b"RIFF\x24\0\0\0WAVEfmt "), Unknown);
+    }
+}
@@ -0,0 +1,460 @@
+//! Incremental compression of async bodies.
+//!
+//! [`CompressStream`] wraps a [`Stream`] of [`Bytes`] chunks and
+//! [`CompressReader`] wraps a tokio [`AsyncRead`]. Both compress with an
+//! [`Encoder`] and emit output according to a [`FlushPolicy`], so streaming
+//! responses such as Server-Sent Events reach the client without waiting for
+//! the codec's internal buffers to fill up.
+//!
+//! ```
+//! # #[cfg(feature = "gzip")]
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! use bytes::Bytes;
+//! use compressible::stream::CompressStream;
+//! use compressible::Encoding;
+//! use futures_util::{stream, StreamExt};
+//!
+//! let events = stream::iter(vec![
+//!     Ok(Bytes::from_static(b"data: one\n\n")),
+//!     Ok(Bytes::from_static(b"data: two\n\n")),
+//! ]);
+//! let body = CompressStream::negotiate(events, "text/event-stream", "gzip", &Encoding::ALL).unwrap();
+//! assert_eq!(body.encoding(), Encoding::Gzip);
+//!
+//! let chunks: Vec<_> = body.collect().await;
+//! assert_eq!(chunks.len(), 3);
+//! # });
+//! ```
+use std::io::{self, Write};
+use std::mem;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use bytes::Bytes;
+use futures_core::Stream;
+use pin_project_lite::pin_project;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::encode::{is_enabled, Encoder, Level};
+use crate::essence::Essence;
+use crate::negotiate::select_encoding_with_heuristics;
+use crate::Encoding;
+
+/// When compressed output is flushed downstream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlushPolicy {
+    /// Only emit output when the codec's buffers fill up and at the end of the
+    /// body. Gives the best compression ratio.
+    End,
+    /// Flush after every input chunk or read, e.g. every Server-Sent Event.
+    EveryChunk,
+    /// Flush once at least this many uncompressed bytes have been consumed
+    /// since the previous flush.
+    EveryBytes(usize),
+}
+
+impl FlushPolicy {
+    /// Returns `FlushPolicy::EveryChunk` for `text/event-stream` and
+    /// `FlushPolicy::End` for every other content type.
+    pub fn for_content_type(content_type: &str) -> Self {
+        let essence = Essence::parse(content_type);
+        if essence.is_some_and(|essence| essence.as_str() == "text/event-stream") {
+            FlushPolicy::EveryChunk
+        } else {
+            FlushPolicy::End
+        }
+    }
+}
+
+/// Tracks consumed input and flushes the encoder when the policy says so.
+#[derive(Debug)]
+struct Flusher {
+    policy: FlushPolicy,
+    unflushed: usize,
+}
+
+impl Flusher {
+    fn write(&mut self, encoder: &mut Encoder<Vec<u8>>, input: &[u8]) -> io::Result<()> {
+        encoder.write_all(input)?;
+        self.unflushed += input.len();
+
+        let flush = match self.policy {
+            FlushPolicy::End => false,
+            FlushPolicy::EveryChunk => true,
+            FlushPolicy::EveryBytes(bytes) => self.unflushed >= bytes,
+        };
+        if flush {
+            encoder.flush()?;
+            self.unflushed = 0;
+        }
+        Ok(())
+    }
+}
+
+/// Picks the encoding for a body with the provided content type, falling back
+/// to `Encoding::Identity` when the client accepts nothing in `supported`.
+/// Encodings whose codec is disabled are skipped, so `&Encoding::ALL` is a
+/// valid `supported` in any build.
+///
+/// Streaming types such as `text/event-stream` are often missing from
+/// mime-db, so this uses [`select_encoding_with_heuristics`].
+fn negotiated(content_type: &str, accept_encoding: &str, supported: &[Encoding]) -> Encoding {
+    let enabled: Vec<Encoding> = supported
+        .iter()
+        .copied()
+        .filter(|&encoding| is_enabled(encoding))
+        .collect();
+    select_encoding_with_heuristics(content_type, accept_encoding, &enabled)
+        .unwrap_or(Encoding::Identity)
+}
+
+pin_project! {
+    /// A [`Stream`] of compressed [`Bytes`] produced from a stream of
+    /// uncompressed chunks.
+    pub struct CompressStream<S> {
+        #[pin]
+        inner: S,
+        encoding: Encoding,
+        encoder: Option<Encoder<Vec<u8>>>,
+        flusher: Flusher,
+    }
+}
+
+impl<S> CompressStream<S>
+where
+    S: Stream<Item = io::Result<Bytes>>,
+{
+    /// Compresses `inner` with `encoding` at the default level, only flushing
+    /// at the end of the stream.
+    pub fn new(inner: S, encoding: Encoding) -> io::Result<Self> {
+        Self::with_level(inner, encoding, Level::Default)
+    }
+
+    /// Compresses `inner` with `encoding` at `level`, only flushing at the end
+    /// of the stream.
+    pub fn with_level(inner: S, encoding: Encoding, level: Level) -> io::Result<Self> {
+        Ok(CompressStream {
+            inner,
+            encoding,
+            encoder: Some(Encoder::new(encoding, level, Vec::new())?),
+            flusher: Flusher {
+                policy: FlushPolicy::End,
+                unflushed: 0,
+            },
+        })
+    }
+
+    /// Compresses a body with the provided content type using the encoding
+    /// the client prefers, and the flush policy from
+    /// [`FlushPolicy::for_content_type`].
+    ///
+    /// Types that are not compressible according to
+    /// [`compressibility_with_heuristics`](crate::compressibility_with_heuristics)
+    /// are left uncompressed, as in [`select_encoding_with_heuristics`].
+    ///
+    /// Check [`CompressStream::encoding`] to set `Content-Encoding`.
+    pub fn negotiate(
+        inner: S,
+        content_type: &str,
+        accept_encoding: &str,
+        supported: &[Encoding],
+    ) -> io::Result<Self> {
+        let encoding = negotiated(content_type, accept_encoding, supported);
+        Ok(Self::new(inner, encoding)?.flush_policy(FlushPolicy::for_content_type(content_type)))
+    }
+
+    /// Sets when compressed output is flushed downstream.
+    pub fn flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flusher.policy = policy;
+        self
+    }
+
+    /// Returns the encoding of the compressed output.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+}
+
+impl<S> Stream for CompressStream<S>
+where
+    S: Stream<Item = io::Result<Bytes>>,
+{
+    type Item = io::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            let encoder = match this.encoder.as_mut() {
+                Some(encoder) => encoder,
+                None => return Poll::Ready(None),
+            };
+
+            let output = match ready!(this.inner.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => {
+                    if let Err(err) = this.flusher.write(encoder, &chunk) {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    mem::take(encoder.get_mut())
+                }
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => match this.encoder.take().expect("encoder is present").finish() {
+                    Ok(output) => output,
+                    Err(err) => return Poll::Ready(Some(Err(err))),
+                },
+            };
+
+            if !output.is_empty() {
+                return Poll::Ready(Some(Ok(Bytes::from(output))));
+            }
+        }
+    }
+}
+
+pin_project! {
+    /// An [`AsyncRead`] producing the compressed contents of an inner reader.
+    pub struct CompressReader<R> {
+        #[pin]
+        inner: R,
+        encoding: Encoding,
+        encoder: Option<Encoder<Vec<u8>>>,
+        flusher: Flusher,
+        input: Box<[u8]>,
+        output: Vec<u8>,
+        position: usize,
+    }
+}
+
+impl<R: AsyncRead> CompressReader<R> {
+    /// Compresses `inner` with `encoding` at the default level, only flushing
+    /// at the end of the input.
+    pub fn new(inner: R, encoding: Encoding) -> io::Result<Self> {
+        Self::with_level(inner, encoding, Level::Default)
+    }
+
+    /// Compresses `inner` with `encoding` at `level`, only flushing at the end
+    /// of the input.
+    pub fn with_level(inner: R, encoding: Encoding, level: Level) -> io::Result<Self> {
+        Ok(CompressReader {
+            inner,
+            encoding,
+            encoder: Some(Encoder::new(encoding, level, Vec::new())?),
+            flusher: Flusher {
+                policy: FlushPolicy::End,
+                unflushed: 0,
+            },
+            input: vec![0; 8 * 1024].into_boxed_slice(),
+            output: Vec::new(),
+            position: 0,
+        })
+    }
+
+    /// Compresses a body with the provided content type using the encoding
+    /// the client prefers, and the flush policy from
+    /// [`FlushPolicy::for_content_type`].
+    ///
+    /// Types that are not compressible according to
+    /// [`compressibility_with_heuristics`](crate::compressibility_with_heuristics)
+    /// are left uncompressed, as in [`select_encoding_with_heuristics`].
+    ///
+    /// Check [`CompressReader::encoding`] to set `Content-Encoding`.
+    pub fn negotiate(
+        inner: R,
+        content_type: &str,
+        accept_encoding: &str,
+        supported: &[Encoding],
+    ) -> io::Result<Self> {
+        let encoding = negotiated(content_type, accept_encoding, supported);
+        Ok(Self::new(inner, encoding)?.flush_policy(FlushPolicy::for_content_type(content_type)))
+    }
+
+    /// Sets when compressed output is flushed. Every read from the inner
+    /// reader counts as a chunk.
+    pub fn flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flusher.policy = policy;
+        self
+    }
+
+    /// Returns the encoding of the compressed output.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for CompressReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let mut this = self.project();
+
+        loop {
+            if *this.position < this.output.len() {
+                let pending = &this.output[*this.position..];
+                let len = pending.len().min(buf.remaining());
+                buf.put_slice(&pending[..len]);
+                *this.position += len;
+                return Poll::Ready(Ok(()));
+            }
+
+            this.output.clear();
+            *this.position = 0;
+
+            let encoder = match this.encoder.as_mut() {
+                Some(encoder) => encoder,
+                None => return Poll::Ready(Ok(())),
+            };
+
+            let mut input = ReadBuf::new(this.input);
+            ready!(this.inner.as_mut().poll_read(cx, &mut input))?;
+
+            if input.filled().is_empty() {
+                *this.output = this.encoder.take().expect("encoder is present").finish()?;
+            } else {
+                this.flusher.write(encoder, input.filled())?;
+                mem::swap(this.output, encoder.get_mut());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{stream, StreamExt};
+    #[cfg(feature = "gzip")]
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    fn chunks() -> Vec<io::Result<Bytes>> {
+        (0..10)
+            .map(|i| Ok(Bytes::from(format!("event: tick\ndata: {}\n\n", i))))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn identity_stream_passes_chunks_through() {
+        let body = CompressStream::new(stream::iter(chunks()), Encoding::Identity).unwrap();
+        let output: Vec<Bytes> = body.map(Result::unwrap).collect().await;
+
+        assert_eq!(output.len(), 10);
+        assert_eq!(output[3], "event: tick\ndata: 3\n\n");
+    }
+
+    #[test]
+    fn flush_policy_for_content_type() {
+        assert_eq!(
+            FlushPolicy::for_content_type("Text/Event-Stream; charset=utf-8"),
+            FlushPolicy::EveryChunk
+        );
+        assert_eq!(FlushPolicy::for_content_type("text/html"), FlushPolicy::End);
+        assert_eq!(
+            FlushPolicy::for_content_type("text/event-stream-ish"),
+            FlushPolicy::End
+        );
+        assert_eq!(FlushPolicy::for_content_type("garbage"), FlushPolicy::End);
+    }
+
+    #[tokio::test]
+    async fn negotiates_enabled_codecs_only() {
+        let body = CompressStream::negotiate(
+            stream::iter(chunks()),
+            "text/html",
+            "br, gzip",
+            &Encoding::ALL,
+        )
+        .unwrap();
+        assert!(is_enabled(body.encoding()));
+        #[cfg(all(feature = "gzip", not(feature = "brotli")))]
+        assert_eq!(body.encoding(), Encoding::Gzip);
+        #[cfg(not(any(feature = "gzip", feature = "brotli")))]
+        assert_eq!(body.encoding(), Encoding::Identity);
+    }
+
+    #[tokio::test]
+    async fn negotiates_identity_for_incompressible_types() {
+        let body =
+            CompressStream::negotiate(stream::iter(chunks()), "image/png", "gzip", &Encoding::ALL)
+                .unwrap();
+        assert_eq!(body.encoding(), Encoding::Identity);
+    }
+
+    #[cfg(feature = "gzip")]
+    fn gunzip(data: &[u8]) -> Vec<u8> {
+        use std::io::Read;
+
+        let mut out = Vec::new();
+        flate2::read::GzDecoder::new(data)
+            .read_to_end(&mut out)
+            .unwrap();
+        out
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn stream_flushes_every_chunk() {
+        let body = CompressStream::new(stream::iter(chunks()), Encoding::Gzip)
+            .unwrap()
+            .flush_policy(FlushPolicy::EveryChunk);
+        let output: Vec<Bytes> = body.map(Result::unwrap).collect().await;
+
+        // One flushed block per chunk, plus the trailer.
+        assert_eq!(output.len(), 11);
+        let expected: Vec<u8> = chunks()
+            .into_iter()
+            .flat_map(|c| c.unwrap().to_vec())
+            .collect();
+        assert_eq!(gunzip(&output.concat()), expected);
+    }
+
+    /// Decompresses a gzip stream that has been flushed but not finished.
+    #[cfg(feature = "gzip")]
+    fn gunzip_partial(data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut decoder = flate2::write::GzDecoder::new(Vec::new());
+        decoder.write_all(data).unwrap();
+        decoder.flush().unwrap();
+        decoder.get_ref().clone()
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn stream_buffers_until_end() {
+        let body = CompressStream::new(stream::iter(chunks()), Encoding::Gzip).unwrap();
+        let output: Vec<Bytes> = body.map(Result::unwrap).collect().await;
+
+        // The gzip header is written eagerly, everything else at the end.
+        assert_eq!(output.len(), 2);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn reader_compresses_duplex_stream() {
+        let (mut client, server) = tokio::io::duplex(64);
+        let mut reader =
+            CompressReader::negotiate(server, "text/event-stream", "gzip", &Encoding::ALL).unwrap();
+        assert_eq!(reader.encoding(), Encoding::Gzip);
+
+        let (resume, resumed) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            client.write_all(b"data: hello\n\n").await.unwrap();
+            client.flush().await.unwrap();
+            resumed.await.unwrap();
+            client.write_all(b"data: world\n\n").await.unwrap();
+        });
+
+        // The first event is readable while the writer is still open.
+        let mut first = vec![0; 256];
+        let n = reader.read(&mut first).await.unwrap();
+        assert_eq!(gunzip_partial(&first[..n]), b"data: hello\n\n");
+        resume.send(()).unwrap();
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).await.unwrap();
+        first.truncate(n);
+        first.extend(rest);
+        assert_eq!(gunzip(&first), b"data: hello\n\ndata: world\n\n");
+    }
+}